@@ -1,9 +1,109 @@
+mod alerts;
 mod assign;
+mod auto_assign;
+mod availability;
+mod caretakers;
+mod clients;
+mod credentials;
+mod delivery;
+mod dnd;
+mod export;
+mod import;
+mod outbox;
+mod services;
+#[cfg(not(target_arch = "wasm32"))] // no sockets on web pages
+mod sync;
+mod timeline;
+
+use std::task::Poll;
+
+use crate::domain::storage::{self, Backend};
+use crate::domain::{ClientId, Store};
 
 trait App {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame);
+    /// `user` is who is signed in, for recording who made a change.
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        user: &str,
+    );
 
     fn get_display_name(&self) -> String;
+
+    /// Persist this app's state. Each app is stored under its display name.
+    fn save(&self, storage: &mut dyn eframe::Storage);
+}
+
+/// A request from one app to show something in another, e.g. clicking a
+/// client in Assign opens their profile in Clients.
+#[derive(Clone, Copy, Debug)]
+enum Open {
+    Client(ClientId),
+}
+
+impl Open {
+    fn id() -> egui::Id {
+        egui::Id::new("open")
+    }
+
+    /// The display name of the app that shows it.
+    fn app(self) -> &'static str {
+        match self {
+            Open::Client(_) => "Clients",
+        }
+    }
+
+    fn request(self, ctx: &egui::Context) {
+        ctx.data_mut(|d| d.insert_temp(Self::id(), self));
+        ctx.request_repaint();
+    }
+
+    fn pending(ctx: &egui::Context) -> Option<Self> {
+        ctx.data_mut(|d| d.get_temp(Self::id()))
+    }
+
+    /// Called by the app that shows it.
+    fn take(ctx: &egui::Context) -> Option<Self> {
+        ctx.data_mut(|d| {
+            let open = d.get_temp(Self::id());
+            d.remove::<Self>(Self::id());
+            open
+        })
+    }
+}
+
+/// Restore an app from storage by its display name, falling back to the default.
+fn restore<A>(storage: Option<&dyn eframe::Storage>) -> Box<dyn App>
+where
+    A: App + Default + serde::de::DeserializeOwned + 'static,
+{
+    let app = A::default();
+    let restored = storage.and_then(|s| eframe::get_value::<A>(s, &app.get_display_name()));
+
+    Box::new(restored.unwrap_or(app))
+}
+
+/// The SQLite database in the user's data directory, which can be backed up
+/// by copying the one file.
+#[cfg(not(target_arch = "wasm32"))]
+fn open_database() -> Result<Box<dyn Backend>, storage::Error> {
+    let dir = directories_next::ProjectDirs::from("", "", "zDoc")
+        .map(|dirs| dirs.data_dir().to_path_buf())
+        .unwrap_or_default();
+    std::fs::create_dir_all(&dir).map_err(|err| storage::Error::Backend(err.to_string()))?;
+    let path = dir.join("zdoc.sqlite");
+    tracing::info!("Keeping data in {}", path.display());
+
+    Ok(Box::new(storage::sqlite::Sqlite::open(path)?))
+}
+
+/// The browser's IndexedDB, so the page keeps its data offline and across
+/// reloads.
+#[cfg(target_arch = "wasm32")]
+fn open_database() -> Result<Box<dyn Backend>, storage::Error> {
+    Ok(Box::new(storage::indexed_db::IndexedDb::open()?))
 }
 
 /**
@@ -20,36 +120,136 @@ pub struct WrapApp {
     #[serde(skip)]
     apps: Vec<Box<dyn App>>,
 
+    /// The data shared by every app. It is kept by the backend rather than
+    /// with the app state, older app state may still hold it though
+    #[serde(default = "Store::sample", skip_serializing)]
+    store: Store,
+
+    /// Where the store is kept, if it could be opened
+    #[serde(skip)]
+    backend: Option<Box<dyn Backend>>,
+
+    /// Whether the backend is still reading the saved store. Nothing is saved
+    /// until it has
+    #[serde(skip)]
+    loading: bool,
+
+    /// The last problem keeping the store, shown until a save succeeds
+    #[serde(skip)]
+    storage_error: Option<String>,
+
     selcted: usize,
+
+    /// Who is using the app, recorded against alerts and other changes
+    user: String,
+
+    #[serde(skip)]
+    import: import::ImportWindow,
+
+    #[serde(skip)]
+    delivery: delivery::DeliveryWindow,
+
+    #[serde(skip)]
+    outbox: outbox::Outbox,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    sync: sync::SyncWindow,
 }
 
 impl WrapApp {
     /// Called once before the first frame.
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // This is also where you can customize the look and feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
 
-        // Until I can store the apps vec, this won't work
-        // if let Some(storage) = cc.storage {
-        //     return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-        // }
+        let mut app: Self = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or(Self {
+                value: 2.7,
+                store: Store::sample(),
+                user: "Coordinator".to_string(),
+                ..Default::default()
+            });
+
+        // The apps vec can't be serialized directly, so each app is restored on its own
+        app.apps = vec![
+            restore::<assign::Assign>(cc.storage),
+            restore::<alerts::Alerts>(cc.storage),
+            restore::<availability::Availability>(cc.storage),
+            restore::<caretakers::Caretakers>(cc.storage),
+            restore::<clients::Clients>(cc.storage),
+            restore::<credentials::Credentials>(cc.storage),
+            restore::<services::Services>(cc.storage),
+            restore::<timeline::Timeline>(cc.storage),
+        ];
+        app.selcted = app.selcted.min(app.apps.len() - 1);
+
+        match open_database() {
+            Ok(backend) => {
+                app.backend = Some(backend);
+                app.loading = true;
+                app.poll_backend();
+            }
+            Err(err) => app.storage_failed(err),
+        }
+
+        app
+    }
+
+    /// Switch to the saved store once the backend has read it. If nothing was
+    /// saved yet, the store restored so far is saved on the next save.
+    fn poll_backend(&mut self) {
+        if !self.loading {
+            return;
+        }
+        let Some(backend) = &mut self.backend else {
+            return;
+        };
+        let Poll::Ready(loaded) = backend.load() else {
+            return;
+        };
 
-        Self {
-            value: 2.7,
-            apps: vec![Box::<assign::Assign>::default()],
-            selcted: 0,
+        self.loading = false;
+        match loaded {
+            Ok(Some(store)) => self.store = store,
+            Ok(None) => {}
+            Err(err) => {
+                // Leave whatever is saved alone rather than overwrite it
+                self.backend = None;
+                self.storage_failed(err);
+            }
         }
     }
 
+    fn storage_failed(&mut self, err: storage::Error) {
+        tracing::error!("Couldn't open storage: {err}");
+        self.storage_error = Some(format!("Changes aren't being kept: {err}"));
+    }
+
     fn menu_bar(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        #[cfg(not(target_arch = "wasm32"))] // no File->Quit on web pages!
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    if ui.button("Import...").clicked() {
+                        self.import.open();
+                        ui.close_menu();
+                    }
+                    if ui.button("Alert delivery...").clicked() {
+                        self.delivery.open();
+                        ui.close_menu();
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button("Sync...").clicked() {
+                        self.sync.open();
+                        ui.close_menu();
+                    }
+
+                    #[cfg(not(target_arch = "wasm32"))] // no File->Quit on web pages!
                     if ui.button("Quit").clicked() {
                         _frame.close();
                     }
@@ -86,6 +286,38 @@ impl WrapApp {
                 self.selcted = selected_anchor;
 
                 ui.separator();
+
+                ui.label("Signed in as");
+                ui.text_edit_singleline(&mut self.user);
+
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    let pending = self.sync.pending();
+                    if pending > 0 {
+                        ui.separator();
+                        let text = egui::RichText::new(format!("{pending} pending changes"))
+                            .color(ui.visuals().warn_fg_color);
+                        if ui
+                            .link(text)
+                            .on_hover_text(
+                                "Assignments made while the sync server couldn't be reached. \
+                                 They are sent when it can be again.",
+                            )
+                            .clicked()
+                        {
+                            self.sync.open();
+                        }
+                    }
+                }
+
+                if self.loading {
+                    ui.separator();
+                    ui.weak("Loading saved data...");
+                }
+                if let Some(err) = &self.storage_error {
+                    ui.separator();
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
             });
     }
 }
@@ -94,6 +326,22 @@ impl eframe::App for WrapApp {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, eframe::APP_KEY, self);
+
+        if let Some(backend) = self.backend.as_mut().filter(|_| !self.loading) {
+            self.storage_error = backend
+                .save(&self.store)
+                .err()
+                .map(|err| format!("Couldn't save: {err}"));
+        }
+
+        for app in &self.apps {
+            app.save(storage);
+        }
+    }
+
+    /// Save often, so little is lost if the app doesn't close cleanly.
+    fn auto_save_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(5)
     }
 
     /// Called each time the UI needs repainting, which may be many times per second.
@@ -111,11 +359,46 @@ impl eframe::App for WrapApp {
         //     frame.set_fullscreen(!frame.info().window_info.fullscreen);
         // }
 
+        if self.loading {
+            self.poll_backend();
+            if self.loading {
+                ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            }
+        }
+
         self.menu_bar(ctx, frame);
 
         self.side_bar(ctx, frame);
 
+        self.store.set_user(self.user.trim());
+
         // Can assume here
-        self.apps[self.selcted].update(ctx, frame);
+        self.apps[self.selcted].update(ctx, frame, &mut self.store, &self.user);
+
+        // Switch to whichever app can show what was asked for, it picks the
+        // request up next frame
+        if let Some(open) = Open::pending(ctx) {
+            if let Some(index) = self
+                .apps
+                .iter()
+                .position(|a| a.get_display_name() == open.app())
+            {
+                self.selcted = index;
+            }
+        }
+
+        self.import.show(ctx, &mut self.store);
+        self.delivery.show(ctx, &mut self.store);
+
+        self.outbox.update(ctx, &mut self.store);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.sync.show(ctx, &mut self.store);
+            self.sync.update(ctx, &mut self.store);
+        }
+        // Nothing to share them with on the web
+        #[cfg(target_arch = "wasm32")]
+        self.store.take_assignment_changes();
     }
 }
@@ -0,0 +1,424 @@
+use chrono::{DateTime, Utc};
+use egui::{Label, RichText, ScrollArea, Sense};
+use egui_extras::{Column, TableBuilder};
+
+use crate::domain::alert::{Action, Severity, Status};
+use crate::domain::delivery;
+use crate::domain::{Alert, AlertId, CaretakerId, Store, TaskId};
+
+/// The alert inbox: every alert raised from Assign, and what has been done
+/// about it.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Alerts {
+    /// Only show alerts in this state, `None` shows all
+    status: Option<Status>,
+    /// Whose notifications are shown
+    caretaker: Option<CaretakerId>,
+    #[serde(skip)]
+    selected: Option<AlertId>,
+    #[serde(skip)]
+    note: String,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl super::App for Alerts {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        user: &str,
+    ) {
+        egui::SidePanel::right("alert_detail")
+            .resizable(true)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                ScrollArea::vertical().show(ui, |ui| {
+                    self.detail_ui(ui, store, user);
+                    ui.separator();
+                    self.notifications_ui(ui, store);
+                });
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label("Alerts");
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.status, None, "All");
+                for status in [Status::Open, Status::Acknowledged, Status::Resolved] {
+                    ui.selectable_value(&mut self.status, Some(status), status.to_string());
+                }
+            });
+
+            ui.separator();
+
+            let mut alerts: Vec<&Alert> = store
+                .alerts()
+                .iter()
+                .filter(|a| self.status.map_or(true, |s| a.status() == s))
+                .collect();
+            alerts.sort_by(|a, b| {
+                (
+                    b.status() != Status::Resolved,
+                    b.severity,
+                    b.raised().map(|e| e.at),
+                )
+                    .cmp(&(
+                        a.status() != Status::Resolved,
+                        a.severity,
+                        a.raised().map(|e| e.at),
+                    ))
+            });
+
+            if alerts.is_empty() {
+                ui.label("No alerts.");
+                return;
+            }
+
+            TableBuilder::new(ui)
+                .striped(true)
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::remainder())
+                .header(20.0, |mut header| {
+                    for title in [
+                        "Raised", "Severity", "Client", "Task", "Status", "Delivery", "Message",
+                    ] {
+                        header.col(|ui| {
+                            ui.heading(title);
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    for alert in alerts {
+                        body.row(30.0, |mut row| {
+                            row.col(|ui| {
+                                let selected = self.selected == Some(alert.id);
+                                let raised = alert.raised().map(|e| time_text(&e.at));
+                                if ui
+                                    .selectable_label(selected, raised.unwrap_or_default())
+                                    .clicked()
+                                {
+                                    self.select(alert.id);
+                                }
+                            });
+                            row.col(|ui| {
+                                severity_label(ui, alert.severity);
+                            });
+                            row.col(|ui| {
+                                if let Some(client) = store.client(alert.client) {
+                                    ui.label(&client.name);
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.label(task_text(store, alert.task));
+                            });
+                            row.col(|ui| {
+                                ui.label(alert.status().to_string());
+                            });
+                            row.col(|ui| {
+                                delivery_label(ui, alert);
+                            });
+                            row.col(|ui| {
+                                if ui
+                                    .add(Label::new(&alert.message).sense(Sense::click()))
+                                    .clicked()
+                                {
+                                    self.select(alert.id);
+                                }
+                            });
+                        });
+                    }
+                });
+        });
+    }
+
+    fn get_display_name(&self) -> String {
+        "Alerts".to_string()
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, &self.get_display_name(), self);
+    }
+}
+
+impl Alerts {
+    fn select(&mut self, id: AlertId) {
+        if self.selected != Some(id) {
+            self.selected = Some(id);
+            self.note.clear();
+            self.error = None;
+        }
+    }
+
+    /// The selected alert's audit trail, and buttons to move it along.
+    fn detail_ui(&mut self, ui: &mut egui::Ui, store: &mut Store, user: &str) {
+        let Some(alert) = self.selected.and_then(|id| store.alert(id)) else {
+            ui.label("Select an alert to see its history.");
+            return;
+        };
+        let id = alert.id;
+        let status = alert.status();
+
+        ui.horizontal(|ui| {
+            severity_label(ui, alert.severity);
+            ui.label(status.to_string());
+        });
+        ui.label(RichText::new(&alert.message).strong());
+        if let Some(client) = store.client(alert.client) {
+            ui.label(format!("{}, {}", client.name, task_text(store, alert.task)));
+        }
+
+        ui.separator();
+
+        egui::Grid::new("alert_history")
+            .striped(true)
+            .num_columns(3)
+            .show(ui, |ui| {
+                for event in &alert.history {
+                    ui.label(time_text(&event.at));
+                    ui.label(&event.by);
+                    let action = match &event.action {
+                        Action::Raised => "Raised".to_string(),
+                        Action::Notified(c) => match store.caretaker(*c) {
+                            Some(c) => format!("Notified {}", c.name),
+                            None => "Notified".to_string(),
+                        },
+                        Action::Acknowledged => "Acknowledged".to_string(),
+                        Action::Resolved => "Resolved".to_string(),
+                    };
+                    if event.note.is_empty() {
+                        ui.label(action);
+                    } else {
+                        ui.label(format!("{action}: {}", event.note));
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if !alert.deliveries.is_empty() {
+            ui.separator();
+            ui.label("Delivery:");
+            let mut retry = None;
+            for (index, delivery) in alert.deliveries.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(delivery.channel.to_string());
+                    ui.label(delivery.to_string());
+                    if delivery.status == delivery::Status::Failed
+                        && ui.small_button("Retry").clicked()
+                    {
+                        retry = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = retry {
+                store.retry_delivery(id, index);
+                return;
+            }
+        }
+
+        if status == Status::Resolved {
+            return;
+        }
+
+        ui.separator();
+
+        ui.label("Note:");
+        ui.text_edit_multiline(&mut self.note);
+
+        let mut result = None;
+        ui.horizontal(|ui| {
+            if status == Status::Open && ui.button("Acknowledge").clicked() {
+                result = Some(store.acknowledge_alert(id, user, self.note.trim()));
+            }
+            if ui.button("Resolve").clicked() {
+                result = Some(store.resolve_alert(id, user, self.note.trim()));
+            }
+        });
+        match result {
+            Some(Ok(())) => {
+                self.note.clear();
+                self.error = None;
+            }
+            Some(Err(err)) => self.error = Some(err.to_string()),
+            None => {}
+        }
+
+        if let Some(err) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+    }
+
+    /// What a caretaker has been told about alerts on their tasks.
+    fn notifications_ui(&mut self, ui: &mut egui::Ui, store: &mut Store) {
+        ui.heading("Notifications");
+
+        if matches!(self.caretaker, Some(c) if store.caretaker(c).is_none()) {
+            self.caretaker = None;
+        }
+        let selected = self
+            .caretaker
+            .and_then(|c| store.caretaker(c))
+            .map_or("Pick a caretaker", |c| c.name.as_str());
+        egui::ComboBox::from_label("Caretaker")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for caretaker in store.caretakers() {
+                    ui.selectable_value(&mut self.caretaker, Some(caretaker.id), &caretaker.name);
+                }
+            });
+
+        let Some(caretaker) = self.caretaker else {
+            return;
+        };
+
+        let mut read = None;
+        let notifications = store.notifications_for(caretaker);
+        if notifications.is_empty() {
+            ui.label("Nothing yet.");
+        }
+        for n in notifications {
+            let Some(alert) = store.alert(n.alert) else {
+                continue;
+            };
+            ui.horizontal(|ui| {
+                let text = RichText::new(format!(
+                    "{} {}: {}",
+                    time_text(&n.at),
+                    alert.severity,
+                    alert.message
+                ));
+                let text = if n.read { text } else { text.strong() };
+                if ui.add(Label::new(text).sense(Sense::click())).clicked() {
+                    self.select(alert.id);
+                }
+                if !n.read && ui.small_button("Mark read").clicked() {
+                    read = Some(n.alert);
+                }
+            });
+        }
+
+        if let Some(alert) = read {
+            store.mark_notification_read(caretaker, alert);
+        }
+    }
+}
+
+/// Raises an alert on a task from Assign.
+#[derive(Default)]
+pub struct RaiseAlertWindow {
+    task: Option<TaskId>,
+    severity: Severity,
+    message: String,
+    error: Option<String>,
+}
+
+impl RaiseAlertWindow {
+    pub fn open(&mut self, task: TaskId) {
+        *self = Self {
+            task: Some(task),
+            ..Self::default()
+        };
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, store: &mut Store, user: &str) {
+        let Some(task) = self.task else {
+            return;
+        };
+
+        let mut open = true;
+        let mut raised = false;
+        egui::Window::new("Raise alert")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if let Some(t) = store.task(task) {
+                    let client = store.client(t.client).map_or("", |c| c.name.as_str());
+                    ui.label(format!("{client}, {}", task_text(store, task)));
+                }
+
+                ui.horizontal(|ui| {
+                    for severity in [Severity::Info, Severity::Warning, Severity::Urgent] {
+                        ui.selectable_value(&mut self.severity, severity, severity.to_string());
+                    }
+                });
+                ui.label("What happened?");
+                ui.text_edit_multiline(&mut self.message);
+
+                if ui.button("Raise").clicked() {
+                    let message = self.message.trim();
+                    self.error = if message.is_empty() {
+                        Some("Describe what happened".to_string())
+                    } else if user.trim().is_empty() {
+                        Some("Enter your name under \"Signed in as\"".to_string())
+                    } else {
+                        match store.raise_alert(task, self.severity, message, user.trim()) {
+                            Ok(_) => {
+                                raised = true;
+                                None
+                            }
+                            Err(err) => Some(err.to_string()),
+                        }
+                    };
+                }
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            });
+
+        if !open || raised {
+            self.task = None;
+        }
+    }
+}
+
+fn severity_label(ui: &mut egui::Ui, severity: Severity) {
+    let color = match severity {
+        Severity::Info => ui.visuals().text_color(),
+        Severity::Warning => ui.visuals().warn_fg_color,
+        Severity::Urgent => ui.visuals().error_fg_color,
+    };
+    ui.colored_label(color, severity.to_string());
+}
+
+/// How sending the alert to its location's channels is going.
+fn delivery_label(ui: &mut egui::Ui, alert: &Alert) {
+    let count =
+        |f: fn(&delivery::Status) -> bool| alert.deliveries.iter().filter(|d| f(&d.status)).count();
+    let total = alert.deliveries.len();
+    let sent = count(|s| matches!(s, delivery::Status::Sent(_)));
+    let failed = count(|s| *s == delivery::Status::Failed);
+
+    if total == 0 {
+        ui.weak("Not sent");
+    } else if failed > 0 {
+        ui.colored_label(ui.visuals().error_fg_color, format!("{failed} failed"));
+    } else if sent == total {
+        ui.label("Sent");
+    } else {
+        ui.label(format!("Sending ({sent}/{total})"));
+    }
+}
+
+/// The task's service and time, e.g. "Medication 08:30-08:45".
+fn task_text(store: &Store, task: TaskId) -> String {
+    let Some(task) = store.task(task) else {
+        return String::new();
+    };
+    let service = store.service(task.service).map_or("", |s| s.name.as_str());
+    format!("{service} {}", task.time_text()).trim().to_string()
+}
+
+fn time_text(at: &DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M").to_string()
+}
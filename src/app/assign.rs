@@ -1,106 +1,116 @@
-use chrono;
-use eframe::epaint;
-use egui::{
-    vec2, CursorIcon, Id, InnerResponse, Label, LayerId, Order, Rect, RichText, ScrollArea, Sense,
-    Shape, Ui, Vec2,
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+use egui::{Id, Label, RichText, ScrollArea, Sense, Ui};
+use egui_extras::{Column, TableBuilder};
+
+use super::alerts::RaiseAlertWindow;
+use super::auto_assign::AutoAssignWindow;
+use super::dnd::{drag_source, drop_target};
+use super::export::ExportWindow;
+use super::Open;
+use crate::domain::{
+    recurrence, Caretaker, CaretakerId, LocationId, Modified, ServiceId, Store, Task,
 };
-use egui_extras::{Column, Table, TableBuilder, TableRow};
 
-#[derive(Debug, Clone)]
-struct Caretaker {
-    name: String,
-    cred: String,
-    case: u32,
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Assign {
+    /// Only show tasks and caretakers at this location, `None` shows all
+    location: Option<LocationId>,
+    date: Option<chrono::NaiveDate>,
+    /// Only show tasks for these services, empty shows all
+    services: BTreeSet<ServiceId>,
+    task_sort: Sort<TaskColumn>,
+    caretaker_sort: Sort<CaretakerColumn>,
+    /// Leave out caretakers who aren't working on the picked date
+    hide_unavailable: bool,
+    #[serde(skip)]
+    dragging_caretaker: Option<CaretakerId>,
+    /// The day whose planned tasks were last made
+    #[serde(skip)]
+    planned: Option<chrono::NaiveDate>,
+    #[serde(skip)]
+    export: ExportWindow,
+    #[serde(skip)]
+    raise_alert: RaiseAlertWindow,
+    #[serde(skip)]
+    auto_assign: AutoAssignWindow,
 }
 
-impl Caretaker {
-    fn new(name: &str, cred: &str) -> Self {
-        Self {
-            name: name.to_string(),
-            cred: cred.to_string(),
-            case: 0,
-        }
-    }
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+enum TaskColumn {
+    Assigned,
+    #[default]
+    Time,
+    Client,
+    Task,
 }
 
-impl ToString for Caretaker {
-    fn to_string(&self) -> String {
-        todo!()
-    }
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+enum CaretakerColumn {
+    #[default]
+    Name,
+    Credentials,
+    CaseCount,
 }
 
-struct Task {
-    assignee: Option<Caretaker>,
-    client_name: String,
-    job: String,
+/// Which column a table is sorted by, and which way.
+#[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
+struct Sort<C> {
+    column: C,
+    ascending: bool,
 }
 
-impl Task {
-    fn new(client_name: &str, job: &str) -> Self {
+impl<C: Default> Default for Sort<C> {
+    fn default() -> Self {
         Self {
-            assignee: None,
-            client_name: String::from(client_name),
-            job: String::from(job),
+            column: C::default(),
+            ascending: true,
         }
     }
 }
 
-pub struct Assign {
-    location: String,
-    date: Option<chrono::NaiveDate>,
-    service: String,
-    sort_by: bool, // true is name, false is age
-    ct_sort_by: bool,
-    dragging_caretaker: Option<Caretaker>,
-    data_l: Vec<Task>,
-    data_r: Vec<Caretaker>,
-}
+impl<C: Copy + PartialEq> Sort<C> {
+    /// A clickable column heading with an arrow on the sorted column. Clicking
+    /// sorts by the column, clicking again reverses it.
+    fn header(&mut self, ui: &mut Ui, title: &str, column: C) {
+        let text = match (self.column == column, self.ascending) {
+            (true, true) => format!("{title} ⬆"),
+            (true, false) => format!("{title} ⬇"),
+            (false, _) => title.to_string(),
+        };
 
-impl Default for Assign {
-    fn default() -> Self {
-        Self {
-            location: Default::default(),
-            date: Default::default(),
-            service: Default::default(),
-            sort_by: Default::default(),
-            ct_sort_by: Default::default(),
-            dragging_caretaker: Default::default(),
-            data_l: vec![
-                Task::new("Angela Johnson", "Dog Therapy"),
-                Task::new("Dax Quil", "Verbal Therapy"),
-                Task::new("Peter Groot", "Teethburshing"),
-                Task::new("Peter Groot", "Dog Therapy"),
-                Task::new("Peter Groot", "Stress Therapy"),
-                Task::new("Peter Groot", "Handwashing"),
-                Task::new("Gamora Thanos", "Stress Therepy"),
-            ],
-            data_r: vec![
-                Caretaker::new("Jim Butz", "Nurse RN"),
-                Caretaker::new("Dave Butz", "DSP"),
-                Caretaker::new("Isaac Butz", "Developer"),
-                Caretaker::new("Steve Butz", "None"),
-            ],
+        if ui
+            .add(Label::new(RichText::new(text).heading()).sense(Sense::click()))
+            .clicked()
+        {
+            if self.column == column {
+                self.ascending = !self.ascending;
+            } else {
+                self.column = column;
+                self.ascending = true;
+            }
         }
     }
-}
 
-#[derive(Debug, Clone, PartialEq)]
-struct Data {
-    name: String,
-    age: u32,
-}
-
-impl Data {
-    fn new(name: &str, age: u32) -> Self {
-        Self {
-            name: name.to_string(),
-            age,
+    fn apply(&self, ordering: std::cmp::Ordering) -> std::cmp::Ordering {
+        if self.ascending {
+            ordering
+        } else {
+            ordering.reverse()
         }
     }
 }
 
 impl super::App for Assign {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        user: &str,
+    ) {
         egui::CentralPanel::default().show(ctx, |ui| {
             // Header
             ui.vertical_centered(|ui| {
@@ -111,36 +121,117 @@ impl super::App for Assign {
 
             // Filter boxes
             ui.horizontal(|ui| {
+                // A restored filter may point at a location that no longer exists
+                if matches!(self.location, Some(l) if store.location(l).is_none()) {
+                    self.location = None;
+                }
+                let selected = self
+                    .location
+                    .and_then(|l| store.location(l))
+                    .map_or("All", |l| l.name.as_str());
                 egui::ComboBox::from_label("Location")
-                    .selected_text(format!("{}", self.location))
+                    .selected_text(selected)
                     .show_ui(ui, |ui| {
                         ui.style_mut().wrap = Some(false);
                         ui.set_min_width(60.0);
-                        ui.selectable_value(&mut self.location, "X".to_string(), "Location X");
-                        ui.selectable_value(&mut self.location, "Y".to_string(), "Location Y");
-                        ui.selectable_value(&mut self.location, "Z".to_string(), "Location Z");
+                        ui.selectable_value(&mut self.location, None, "All");
+                        for location in store.locations() {
+                            ui.selectable_value(
+                                &mut self.location,
+                                Some(location.id),
+                                &location.name,
+                            );
+                        }
                     });
 
                 ui.separator();
 
                 #[cfg(feature = "chrono")]
                 {
-                    let date = self
-                        .date
-                        .get_or_insert_with(|| chrono::offset::Utc::now().date_naive());
+                    let today = chrono::offset::Utc::now().date_naive();
+                    let date = self.date.get_or_insert(today);
+
+                    if ui.button("◀").on_hover_text("Previous day").clicked() {
+                        *date = date.pred_opt().unwrap_or(*date);
+                    }
                     ui.add(egui_extras::DatePickerButton::new(date));
+                    if ui.button("▶").on_hover_text("Next day").clicked() {
+                        *date = date.succ_opt().unwrap_or(*date);
+                    }
+                    if ui.button("Today").clicked() {
+                        *date = today;
+                    }
+
+                    if ui
+                        .button("Copy yesterday")
+                        .on_hover_text(
+                            "Give unassigned tasks the caretaker who had them the day before",
+                        )
+                        .clicked()
+                    {
+                        if let Some(yesterday) = date.pred_opt() {
+                            store.copy_assignments(yesterday, *date);
+                        }
+                    }
+                    plan_button(ui, store, *date);
                     ui.separator();
                 }
 
+                // Make the day's tasks from the care plans. Occurrences that
+                // were already made, and any changes to them, are left alone
+                if let Some(date) = self.date {
+                    plan_day(store, date, &mut self.planned);
+                }
+
+                // Drop any restored services that have left the catalog
+                self.services.retain(|&id| store.service(id).is_some());
+                let selected = match self.services.len() {
+                    0 => "All".to_string(),
+                    1 => self
+                        .services
+                        .iter()
+                        .find_map(|&id| store.service(id))
+                        .map(|s| s.name.clone())
+                        .unwrap_or_default(),
+                    n => format!("{n} services"),
+                };
                 egui::ComboBox::from_label("Service")
-                    .selected_text(format!("{}", self.service))
+                    .selected_text(selected)
                     .show_ui(ui, |ui| {
                         ui.style_mut().wrap = Some(false);
                         ui.set_min_width(60.0);
-                        ui.selectable_value(&mut self.service, "All".to_string(), "All");
-                        ui.selectable_value(&mut self.service, "Some".to_string(), "Some");
-                        ui.selectable_value(&mut self.service, "None".to_string(), "None");
+                        if ui
+                            .selectable_label(self.services.is_empty(), "All")
+                            .clicked()
+                        {
+                            self.services.clear();
+                        }
+                        for service in store.services() {
+                            let mut checked = self.services.contains(&service.id);
+                            if ui.checkbox(&mut checked, &service.name).changed() {
+                                if checked {
+                                    self.services.insert(service.id);
+                                } else {
+                                    self.services.remove(&service.id);
+                                }
+                            }
+                        }
                     });
+                ui.checkbox(&mut self.hide_unavailable, "Hide unavailable")
+                    .on_hover_text("Hide caretakers who aren't working on this date");
+                ui.separator();
+
+                if ui
+                    .button("Auto-assign")
+                    .on_hover_text("Propose caretakers for the unassigned tasks shown")
+                    .clicked()
+                {
+                    let proposals = store.propose_assignments(self.visible_tasks(store));
+                    self.auto_assign.open(proposals);
+                }
+                if ui.button("Export...").clicked() {
+                    self.export.open();
+                }
             });
 
             ui.separator();
@@ -150,6 +241,16 @@ impl super::App for Assign {
 
             let mut src_row = None;
             let mut dst_row = None;
+            let mut clear_row = None;
+
+            let tasks = self.visible_tasks(store);
+
+            // Found up front so the task rows can tell whether they accept the drop
+            self.dragging_caretaker =
+                store.caretakers().iter().map(|c| c.id).find(|&id| {
+                    ui.memory(|mem| mem.is_being_dragged(Assign::caretaker_drag_id(id)))
+                });
+            let dragged = self.dragging_caretaker.and_then(|id| store.caretaker(id));
 
             TableBuilder::new(ui)
                 .striped(false)
@@ -167,60 +268,141 @@ impl super::App for Assign {
                                     .column(Column::auto().resizable(true))
                                     .column(Column::auto().resizable(true))
                                     .column(Column::auto().resizable(true))
+                                    .column(Column::auto().resizable(true))
                                     .column(Column::remainder())
                                     .header(20.0, |mut header| {
                                         header.col(|ui| {
-                                            if ui.heading("Assigned").clicked() {
-                                                self.sort_by = true;
-                                            }
+                                            self.task_sort.header(
+                                                ui,
+                                                "Assigned",
+                                                TaskColumn::Assigned,
+                                            );
                                         });
                                         header.col(|ui| {
-                                            if ui.heading("Client Name").clicked() {
-                                                self.sort_by = true;
-                                            }
+                                            self.task_sort.header(ui, "Time", TaskColumn::Time);
                                         });
                                         header.col(|ui| {
-                                            if ui.heading("Task").clicked() {
-                                                self.sort_by = true;
-                                            }
+                                            self.task_sort.header(
+                                                ui,
+                                                "Client Name",
+                                                TaskColumn::Client,
+                                            );
+                                        });
+                                        header.col(|ui| {
+                                            self.task_sort.header(ui, "Task", TaskColumn::Task);
                                         });
                                         header.col(|ui| {
                                             ui.heading("Alert?");
                                         });
                                     })
                                     .body(|mut body| {
-                                        for (i, d) in self.data_l.iter().enumerate() {
+                                        for d in &tasks {
                                             body.row(30.0, |mut row| {
+                                                let refusal = dragged
+                                                    .and_then(|ct| Assign::refusal(store, d, ct));
+                                                let can_accept = refusal.is_none();
+                                                let warning = dragged
+                                                    .and_then(|ct| Assign::warning(store, d, ct));
+
                                                 row.col(|ui| {
+                                                    let assignee =
+                                                        d.assignee.and_then(|a| store.caretaker(a));
                                                     let response =
-                                                        Assign::drop_target(ui, true, |ui| {
-                                                            ui.label(match &d.assignee {
-                                                                Some(a) => a.name.clone(),
-                                                                None => "".to_string(),
-                                                            });
-                                                        })
-                                                        .response;
+                                                        drop_target(ui, can_accept, |ui| {
+                                                            ui.horizontal(|ui| {
+                                                                Assign::assignee_ui(
+                                                                    ui,
+                                                                    assignee,
+                                                                    d.modified.as_ref(),
+                                                                )
+                                                            })
+                                                            .inner
+                                                        });
+                                                    if response.inner {
+                                                        clear_row = Some(d.id);
+                                                    }
+                                                    let response = response.response;
                                                     let is_being_dragged = ui.memory(|mem| {
                                                         mem.is_anything_being_dragged()
                                                     });
-                                                    if is_being_dragged
-                                                        && true
-                                                        && response.hovered()
-                                                    {
-                                                        dst_row = Some(i);
+                                                    if is_being_dragged && response.hovered() {
+                                                        if can_accept {
+                                                            dst_row = Some(d.id);
+                                                            if let Some(warning) = &warning {
+                                                                egui::show_tooltip_at_pointer(
+                                                                    ui.ctx(),
+                                                                    Id::new("warned drop"),
+                                                                    |ui| {
+                                                                        ui.colored_label(
+                                                                            ui.visuals()
+                                                                                .warn_fg_color,
+                                                                            warning,
+                                                                        );
+                                                                    },
+                                                                );
+                                                            }
+                                                        } else if let Some(refusal) = &refusal {
+                                                            egui::show_tooltip_at_pointer(
+                                                                ui.ctx(),
+                                                                Id::new("refused drop"),
+                                                                |ui| {
+                                                                    ui.label(refusal);
+                                                                },
+                                                            );
+                                                        }
                                                     }
                                                 });
                                                 row.col(|ui| {
-                                                    ui.label(&d.client_name);
+                                                    let clashes = store.double_bookings(d);
+                                                    if clashes.is_empty() {
+                                                        ui.label(d.time_text());
+                                                    } else {
+                                                        ui.colored_label(
+                                                            ui.visuals().error_fg_color,
+                                                            format!("⚠ {}", d.time_text()),
+                                                        )
+                                                        .on_hover_text(format!(
+                                                            "Double-booked with {}",
+                                                            Assign::task_list(store, &clashes)
+                                                        ));
+                                                    }
                                                 });
                                                 row.col(|ui| {
-                                                    ui.label(&d.job);
+                                                    if let Some(client) = store.client(d.client) {
+                                                        if ui
+                                                            .link(&client.name)
+                                                            .on_hover_text("Open profile")
+                                                            .clicked()
+                                                        {
+                                                            Open::Client(client.id)
+                                                                .request(ui.ctx());
+                                                        }
+                                                    }
                                                 });
                                                 row.col(|ui| {
-                                                    if ui.button("Alert").clicked() {
-                                                        Assign::send_alert();
+                                                    if let Some(service) = store.service(d.service)
+                                                    {
+                                                        ui.label(&service.name);
                                                     }
                                                 });
+                                                row.col(|ui| {
+                                                    ui.horizontal(|ui| {
+                                                        if ui.button("Alert").clicked() {
+                                                            self.raise_alert.open(d.id);
+                                                        }
+                                                        let open =
+                                                            store.unresolved_alerts(d.id).count();
+                                                        if open > 0 {
+                                                            ui.colored_label(
+                                                                ui.visuals().warn_fg_color,
+                                                                format!("⚠ {open}"),
+                                                            )
+                                                            .on_hover_text(
+                                                                "Unresolved alerts, see Alerts",
+                                                            );
+                                                        }
+                                                    });
+                                                });
                                             });
                                         }
                                     });
@@ -229,15 +411,7 @@ impl super::App for Assign {
 
                         // Right side
                         b_row.col(|ui| {
-                            // Sorting state
-
-                            self.data_r.sort_by(|a, b| {
-                                if self.ct_sort_by {
-                                    a.name.cmp(&b.name)
-                                } else {
-                                    b.cred.cmp(&a.cred)
-                                }
-                            });
+                            let caretakers = self.visible_caretakers(store);
 
                             // Must wrap in a push id to prevent id overlap
                             ui.push_id("Col 2", |ui| {
@@ -246,45 +420,105 @@ impl super::App for Assign {
                                         .striped(true)
                                         .column(Column::auto().resizable(true))
                                         .column(Column::auto().resizable(true))
+                                        .column(Column::auto().resizable(true))
                                         .column(Column::remainder())
                                         .header(20.0, |mut header| {
                                             header.col(|ui| {
-                                                if ui.heading("Caretaker").clicked() {
-                                                    self.ct_sort_by = true;
-                                                }
+                                                self.caretaker_sort.header(
+                                                    ui,
+                                                    "Caretaker",
+                                                    CaretakerColumn::Name,
+                                                );
                                             });
                                             header.col(|ui| {
-                                                if ui.heading("Credentials").clicked() {
-                                                    self.ct_sort_by = false;
-                                                }
+                                                self.caretaker_sort.header(
+                                                    ui,
+                                                    "Credentials",
+                                                    CaretakerColumn::Credentials,
+                                                );
                                             });
                                             header.col(|ui| {
-                                                if ui.heading("Case Count").clicked() {
-                                                    self.ct_sort_by = false;
-                                                }
+                                                self.caretaker_sort.header(
+                                                    ui,
+                                                    "Case Count",
+                                                    CaretakerColumn::CaseCount,
+                                                );
+                                            });
+                                            header.col(|ui| {
+                                                ui.heading("Hours");
                                             });
                                         })
                                         .body(|mut body| {
-                                            let id_source = "id";
-                                            for (i, d) in self.data_r.iter().enumerate() {
+                                            for d in &caretakers {
                                                 body.row(30.0, |mut row| {
-                                                    let item_id = Id::new(id_source).with(i);
+                                                    let item_id = Assign::caretaker_drag_id(d.id);
                                                     row.col(|ui| {
-                                                        Assign::drag_source(ui, item_id, |ui| {
+                                                        drag_source(ui, item_id, |ui| {
                                                             ui.label(&d.name);
                                                         });
 
                                                         if ui.memory(|mem| {
                                                             mem.is_being_dragged(item_id)
                                                         }) {
-                                                            src_row = Some(i);
+                                                            src_row = Some(d.id);
                                                         }
                                                     });
                                                     row.col(|ui| {
-                                                        ui.label(&d.cred);
+                                                        let lapsed = self
+                                                            .date
+                                                            .map(|date| d.lapsed_on(date))
+                                                            .unwrap_or_default();
+                                                        if lapsed.is_empty() {
+                                                            ui.label(d.credentials_text());
+                                                        } else {
+                                                            let names: Vec<_> = lapsed
+                                                                .iter()
+                                                                .map(|c| c.name.as_str())
+                                                                .collect();
+                                                            ui.colored_label(
+                                                                ui.visuals().warn_fg_color,
+                                                                format!(
+                                                                    "⚠ {}",
+                                                                    d.credentials_text()
+                                                                ),
+                                                            )
+                                                            .on_hover_text(format!(
+                                                                "Not in force on this date: {}",
+                                                                names.join(", ")
+                                                            ));
+                                                        }
                                                     });
                                                     row.col(|ui| {
-                                                        ui.label(&d.case.to_string());
+                                                        let count = store
+                                                            .case_count(d.id, self.date)
+                                                            .to_string();
+                                                        if store.is_double_booked(d.id, self.date) {
+                                                            ui.colored_label(
+                                                                ui.visuals().error_fg_color,
+                                                                format!("{count} ⚠"),
+                                                            )
+                                                            .on_hover_text(
+                                                                "Has tasks at the same time",
+                                                            );
+                                                        } else {
+                                                            ui.label(count);
+                                                        }
+                                                    });
+                                                    row.col(|ui| {
+                                                        match self.date.map(|date| {
+                                                            d.availability.shift_on(date)
+                                                        }) {
+                                                            Some(Ok(shift)) => {
+                                                                ui.label(shift.to_string());
+                                                            }
+                                                            Some(Err(reason)) => {
+                                                                ui.colored_label(
+                                                                    ui.visuals().warn_fg_color,
+                                                                    format!("⚠ {reason}"),
+                                                                );
+                                                            }
+                                                            None => {}
+                                                        }
                                                     });
                                                 });
                                             }
@@ -292,125 +526,228 @@ impl super::App for Assign {
                                 });
                             });
                         });
-                        // FOr commit
                     });
                 });
 
+            if let Some(task) = clear_row {
+                if let Err(err) = store.unassign(task) {
+                    tracing::warn!("Task could not be unassigned: {err}");
+                }
+            }
+
             if let Some(sr) = src_row {
                 if let Some(dr) = dst_row {
                     if ui.input(|i| i.pointer.any_released()) {
-                        self.data_l[dr].assignee = Some(self.data_r[sr].clone());
-                        self.data_r[sr].case += 1;
-
                         // do the drop:
+                        if let Err(err) = store.assign(dr, sr) {
+                            tracing::warn!("Dropped caretaker could not be assigned: {err}");
+                        }
                     }
                 }
             }
         });
+
+        let title = match self.date {
+            Some(date) => format!("Schedule {date}"),
+            None => "Schedule".to_string(),
+        };
+        self.export
+            .show(ctx, store, &title, &self.visible_tasks(store));
+        self.raise_alert.show(ctx, store, user);
+        self.auto_assign.show(ctx, store);
     }
 
     fn get_display_name(&self) -> String {
         "Assign".to_string()
     }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, &self.get_display_name(), self);
+    }
 }
 
 impl Assign {
-    fn send_alert() {}
+    /// The tasks shown in the table, in the order they are shown.
+    fn visible_tasks<'a>(&self, store: &'a Store) -> Vec<&'a Task> {
+        let mut tasks: Vec<&Task> = store
+            .tasks()
+            .iter()
+            .filter(|t| self.location.is_none() || t.location == self.location)
+            .filter(|t| self.date.map_or(true, |d| t.is_on(d)))
+            .filter(|t| self.services.is_empty() || self.services.contains(&t.service))
+            .collect();
+
+        let assignee = |t: &Task| t.assignee.and_then(|a| store.caretaker(a)).map(|a| &a.name);
+        let client = |t: &Task| store.client(t.client).map(|c| &c.name);
+        let service = |t: &Task| store.service(t.service).map(|s| &s.name);
+        tasks.sort_by(|a, b| {
+            self.task_sort.apply(match self.task_sort.column {
+                TaskColumn::Assigned => assignee(a).cmp(&assignee(b)),
+                TaskColumn::Time => (a.date, a.start).cmp(&(b.date, b.start)),
+                TaskColumn::Client => client(a).cmp(&client(b)),
+                TaskColumn::Task => service(a).cmp(&service(b)),
+            })
+        });
 
-    fn drag_source(ui: &mut Ui, id: Id, body: impl FnOnce(&mut Ui)) {
-        let is_being_dragged = ui.memory(|mem| mem.is_being_dragged(id));
+        tasks
+    }
 
-        if !is_being_dragged {
-            let response = ui.scope(body).response;
+    /// The active caretakers working at the selected location, and on the
+    /// picked date when unavailable ones are hidden, in the order they are shown.
+    fn visible_caretakers<'a>(&self, store: &'a Store) -> Vec<&'a Caretaker> {
+        let mut caretakers: Vec<&Caretaker> = store
+            .caretakers()
+            .iter()
+            .filter(|c| c.active)
+            .filter(|c| self.location.is_none() || c.location == self.location)
+            .filter(|c| {
+                !self.hide_unavailable
+                    || self
+                        .date
+                        .map_or(true, |d| c.availability.shift_on(d).is_ok())
+            })
+            .collect();
+
+        caretakers.sort_by(|a, b| {
+            self.caretaker_sort.apply(match self.caretaker_sort.column {
+                CaretakerColumn::Name => a.name.cmp(&b.name),
+                CaretakerColumn::Credentials => a.credentials_text().cmp(&b.credentials_text()),
+                CaretakerColumn::CaseCount => store
+                    .case_count(a.id, self.date)
+                    .cmp(&store.case_count(b.id, self.date)),
+            })
+        });
 
-            // Check for drags:
-            let response = ui.interact(response.rect, id, Sense::drag());
-            if response.hovered() {
-                ui.ctx().set_cursor_icon(CursorIcon::Grab);
-            }
-        } else {
-            ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
-
-            // Paint the body to a new layer:
-            let layer_id = LayerId::new(Order::Tooltip, id);
-            let response = ui.with_layer_id(layer_id, body).response;
-
-            // Now we move the visuals of the body to where the mouse is.
-            // Normally you need to decide a location for a widget first,
-            // because otherwise that widget cannot interact with the mouse.
-            // However, a dragged component cannot be interacted with anyway
-            // (anything with `Order::Tooltip` always gets an empty [`Response`])
-            // So this is fine!
-
-            if let Some(pointer_pos) = ui.ctx().pointer_interact_pos() {
-                let delta = pointer_pos - response.rect.center();
-                ui.ctx().translate_layer(layer_id, delta);
-            }
+        caretakers
+    }
+
+    /// Why `caretaker` can't be dropped on `task`, if they can't.
+    pub(super) fn refusal(store: &Store, task: &Task, caretaker: &Caretaker) -> Option<String> {
+        if !caretaker.active {
+            return Some(format!("{} is no longer active", caretaker.name));
+        }
+
+        let missing = store.missing_credentials(task, caretaker);
+        if !missing.is_empty() {
+            return Some(format!(
+                "{} is missing {}",
+                caretaker.name,
+                missing.join(", ")
+            ));
+        }
+
+        let clashes = store.clashes(task, caretaker.id);
+        if !clashes.is_empty() {
+            return Some(format!(
+                "{} is busy with {}",
+                caretaker.name,
+                Assign::task_list(store, &clashes)
+            ));
         }
+
+        None
     }
 
-    pub fn drop_target<R>(
-        ui: &mut Ui,
-        can_accept_what_is_being_dragged: bool,
-        body: impl FnOnce(&mut Ui) -> R,
-    ) -> InnerResponse<R> {
-        let is_being_dragged = ui.memory(|mem| mem.is_anything_being_dragged());
-
-        let margin = Vec2::splat(4.0);
-
-        let outer_rect_bounds = ui.available_rect_before_wrap();
-        let inner_rect = outer_rect_bounds.shrink2(margin);
-        let where_to_put_background = ui.painter().add(Shape::Noop);
-        let mut content_ui = ui.child_ui(inner_rect, *ui.layout());
-        let ret = body(&mut content_ui);
-        let outer_rect =
-            Rect::from_min_max(outer_rect_bounds.min, content_ui.min_rect().max + margin);
-        let (rect, response) = ui.allocate_at_least(outer_rect.size(), Sense::hover());
-
-        let style = if is_being_dragged && can_accept_what_is_being_dragged && response.hovered() {
-            ui.visuals().widgets.active
-        } else {
-            ui.visuals().widgets.inactive
+    /// What to look out for when `caretaker` is dropped on `task`, though they
+    /// can be.
+    fn warning(store: &Store, task: &Task, caretaker: &Caretaker) -> Option<String> {
+        let expired = store.expired_credentials(task, caretaker);
+        if expired.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "{}'s {} won't be in force on {}",
+            caretaker.name,
+            expired.join(", "),
+            task.date.map(|d| d.to_string()).unwrap_or_default()
+        ))
+    }
+
+    /// "Dog Therapy 09:00-10:00, Handwashing 12:00-12:15"
+    pub(super) fn task_list(store: &Store, tasks: &[&Task]) -> String {
+        tasks
+            .iter()
+            .map(|t| {
+                let service = store.service(t.service).map_or("", |s| s.name.as_str());
+                format!("{service} {}", t.time_text())
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn caretaker_drag_id(id: CaretakerId) -> Id {
+        Id::new("id").with(id)
+    }
+
+    /// The assignee's name with ways to clear it, and who assigned them on
+    /// hover. Returns true if it should be cleared.
+    fn assignee_ui(ui: &mut Ui, assignee: Option<&Caretaker>, modified: Option<&Modified>) -> bool {
+        let Some(assignee) = assignee else {
+            return false;
         };
 
-        let mut fill = style.bg_fill;
-        let mut stroke = style.bg_stroke;
-        if is_being_dragged && !can_accept_what_is_being_dragged {
-            fill = ui.visuals().gray_out(fill);
-            stroke.color = ui.visuals().gray_out(stroke.color);
+        let mut clear = false;
+        let mut response = ui.add(Label::new(&assignee.name).sense(Sense::click()));
+        if let Some(modified) = modified {
+            response = response.on_hover_text(modified_text(modified));
         }
+        response.context_menu(|ui| {
+            if ui.button("Unassign").clicked() {
+                clear = true;
+                ui.close_menu();
+            }
+        });
+        if ui
+            .small_button("✖")
+            .on_hover_text(format!("Unassign {}", assignee.name))
+            .clicked()
+        {
+            clear = true;
+        }
+
+        clear
+    }
+}
 
-        ui.painter().set(
-            where_to_put_background,
-            epaint::RectShape {
-                rounding: style.rounding,
-                fill,
-                stroke,
-                rect,
-            },
-        );
-
-        InnerResponse::new(ret, response)
+/// Make `date`'s tasks from the care plans when it is first shown, if it is
+/// within the planning horizon. Just looking at a day doesn't fill it.
+pub(super) fn plan_day(store: &mut Store, date: NaiveDate, planned: &mut Option<NaiveDate>) {
+    if *planned == Some(date) {
+        return;
+    }
+    *planned = Some(date);
+    let today = chrono::offset::Utc::now().date_naive();
+    if recurrence::in_horizon(today, date) {
+        store.generate_tasks(date);
     }
+}
 
-    // fn ui(&mut self, ui: &mut Ui) {
-    //     ui.columns(self.columns.len(), |uis| {
-    //         for (col_idx, column) in self.columns.clone().into_iter().enumerate() {
-    //             let ui = &mut uis[col_idx];
-    //             let can_accept_what_is_being_dragged = true; // We accept anything being dragged (for now) ¯\_(ツ)_/¯
-    //             let response = Assign::drop_target(ui, can_accept_what_is_being_dragged, |ui| {
-    //                 ui.set_min_size(vec2(64.0, 100.0));
-    //                 for (row_idx, item) in column.iter().enumerate() {
-    //                     Assign::drag_source(ui, item_id, |ui| {
-    //                         ui.add(Label::new(item).sense(Sense::click()));
-    //                     });
-
-    //                 }
-    //             })
-    //             .response;
-
-    //         }
-    //     });
-
-    // }
+/// A button to make `date`'s tasks from the care plans again, e.g. after a
+/// plan changed.
+pub(super) fn plan_button(ui: &mut Ui, store: &mut Store, date: NaiveDate) {
+    let today = chrono::offset::Utc::now().date_naive();
+    if ui
+        .add_enabled(
+            recurrence::in_horizon(today, date),
+            egui::Button::new("Make planned tasks"),
+        )
+        .on_hover_text("Add the tasks the care plans call for on this day")
+        .on_disabled_hover_text(format!(
+            "Only today and the next {} days are planned",
+            recurrence::HORIZON_DAYS
+        ))
+        .clicked()
+    {
+        store.generate_tasks(date);
+    }
+}
+
+/// "Assigned by Ann, 2023-04-01 09:30".
+fn modified_text(modified: &Modified) -> String {
+    let at = modified.at.format("%Y-%m-%d %H:%M");
+    if modified.by.is_empty() {
+        format!("Assigned {at}")
+    } else {
+        format!("Assigned by {}, {at}", modified.by)
+    }
 }
@@ -0,0 +1,151 @@
+use egui_extras::{Column, TableBuilder};
+
+use crate::domain::auto_assign::Proposal;
+use crate::domain::Store;
+
+/// Previews an auto-assign proposal as a diff of the task table, and applies
+/// the rows the coordinator keeps.
+#[derive(Default)]
+pub struct AutoAssignWindow {
+    /// Each proposed change and whether it is ticked to be accepted
+    proposals: Vec<(Proposal, bool)>,
+    open: bool,
+}
+
+impl AutoAssignWindow {
+    pub fn open(&mut self, proposals: Vec<Proposal>) {
+        self.proposals = proposals
+            .into_iter()
+            .map(|p| (p, p.caretaker.is_some()))
+            .collect();
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, store: &mut Store) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut accept = None;
+        egui::Window::new("Auto-assign")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if self.proposals.is_empty() {
+                    ui.label("Every task shown is already assigned.");
+                    return;
+                }
+
+                let placed = self
+                    .proposals
+                    .iter()
+                    .filter(|(p, _)| p.caretaker.is_some())
+                    .count();
+                ui.label(format!(
+                    "Found caretakers for {placed} of {} unassigned task(s).",
+                    self.proposals.len()
+                ));
+
+                ui.separator();
+
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .column(Column::auto())
+                    .column(Column::auto().resizable(true))
+                    .column(Column::auto().resizable(true))
+                    .column(Column::auto().resizable(true))
+                    .column(Column::remainder())
+                    .header(20.0, |mut header| {
+                        for title in ["", "Time", "Client Name", "Task", "Assigned"] {
+                            header.col(|ui| {
+                                ui.heading(title);
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        for (proposal, keep) in &mut self.proposals {
+                            let Some(task) = store.task(proposal.task) else {
+                                continue;
+                            };
+                            body.row(24.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.add_enabled(
+                                        proposal.caretaker.is_some(),
+                                        egui::Checkbox::new(keep, ""),
+                                    );
+                                });
+                                row.col(|ui| {
+                                    ui.label(task.time_text());
+                                });
+                                row.col(|ui| {
+                                    if let Some(client) = store.client(task.client) {
+                                        ui.label(&client.name);
+                                    }
+                                });
+                                row.col(|ui| {
+                                    if let Some(service) = store.service(task.service) {
+                                        ui.label(&service.name);
+                                    }
+                                });
+                                row.col(|ui| {
+                                    match proposal.caretaker.and_then(|c| store.caretaker(c)) {
+                                        Some(caretaker) => {
+                                            let color = if *keep {
+                                                egui::Color32::from_rgb(0, 160, 0)
+                                            } else {
+                                                ui.visuals().weak_text_color()
+                                            };
+                                            ui.colored_label(
+                                                color,
+                                                format!("+ {}", caretaker.name),
+                                            );
+                                        }
+                                        None => {
+                                            ui.weak("No one available");
+                                        }
+                                    }
+                                });
+                            });
+                        }
+                    });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Accept all").clicked() {
+                        accept = Some(true);
+                    }
+                    if ui.button("Accept selected").clicked() {
+                        accept = Some(false);
+                    }
+                    if ui.button("Discard").clicked() {
+                        self.open = false;
+                    }
+                });
+            });
+
+        if let Some(all) = accept {
+            for (proposal, keep) in &self.proposals {
+                let Some(caretaker) = proposal.caretaker else {
+                    continue;
+                };
+                // Leave tasks that were assigned by hand since the proposal
+                let taken = store
+                    .task(proposal.task)
+                    .map_or(true, |t| t.assignee.is_some());
+                if taken || !(all || *keep) {
+                    continue;
+                }
+                if let Err(err) = store.assign(proposal.task, caretaker) {
+                    tracing::warn!("Proposed caretaker could not be assigned: {err}");
+                }
+            }
+            self.open = false;
+        }
+
+        self.open &= open;
+        if !self.open {
+            self.proposals.clear();
+        }
+    }
+}
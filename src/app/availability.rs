@@ -0,0 +1,179 @@
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use egui::{DragValue, ScrollArea};
+
+use crate::domain::availability::{Shift, TimeOff};
+use crate::domain::{CaretakerId, Store};
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// Each caretaker's working hours and time off.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Availability {
+    caretaker: Option<CaretakerId>,
+    /// The time off being added
+    #[serde(skip)]
+    from: Option<NaiveDate>,
+    #[serde(skip)]
+    to: Option<NaiveDate>,
+    #[serde(skip)]
+    reason: String,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl super::App for Availability {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        _user: &str,
+    ) {
+        egui::SidePanel::left("availability_caretakers")
+            .resizable(true)
+            .show(ctx, |ui| {
+                ScrollArea::vertical().show(ui, |ui| {
+                    for caretaker in store.caretakers().iter().filter(|c| c.active) {
+                        if ui
+                            .selectable_label(self.caretaker == Some(caretaker.id), &caretaker.name)
+                            .clicked()
+                        {
+                            self.caretaker = Some(caretaker.id);
+                            self.error = None;
+                        }
+                    }
+                });
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label("Availability");
+            });
+
+            ui.separator();
+
+            let Some(caretaker) = self.caretaker.and_then(|id| store.caretaker_mut(id)) else {
+                ui.label("Pick a caretaker to edit their hours.");
+                return;
+            };
+            ui.heading(&caretaker.name);
+            let availability = &mut caretaker.availability;
+
+            ui.label("Weekly hours");
+            egui::Grid::new("week").striped(true).show(ui, |ui| {
+                for (day, shift) in WEEKDAYS.iter().zip(&mut availability.week) {
+                    let mut works = shift.is_some();
+                    if ui.checkbox(&mut works, *day).changed() {
+                        *shift = works.then(Shift::all_day);
+                    }
+                    if let Some(shift) = shift {
+                        ui.horizontal(|ui| {
+                            time_edit(ui, &mut shift.start);
+                            ui.label("to");
+                            time_edit(ui, &mut shift.end);
+                            if ui.small_button("All day").clicked() {
+                                *shift = Shift::all_day();
+                            }
+                        });
+                        if shift.end <= shift.start {
+                            ui.colored_label(ui.visuals().error_fg_color, "Ends before it starts");
+                        }
+                    } else {
+                        ui.weak("Day off");
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+
+            ui.label("Time off");
+            let mut remove = None;
+            egui::Grid::new("time_off").striped(true).show(ui, |ui| {
+                for (index, off) in availability.time_off.iter().enumerate() {
+                    if off.from == off.to {
+                        ui.label(off.from.to_string());
+                    } else {
+                        ui.label(format!("{} to {}", off.from, off.to));
+                    }
+                    ui.label(&off.reason);
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = remove {
+                availability.time_off.remove(index);
+            }
+
+            #[cfg(feature = "chrono")]
+            ui.horizontal(|ui| {
+                let today = chrono::offset::Utc::now().date_naive();
+                let from = self.from.get_or_insert(today);
+                ui.label("From");
+                ui.add(egui_extras::DatePickerButton::new(from).id_source("time_off_from"));
+                let to = self.to.get_or_insert(today);
+                ui.label("to");
+                ui.add(egui_extras::DatePickerButton::new(to).id_source("time_off_to"));
+                ui.label("Reason:");
+                ui.text_edit_singleline(&mut self.reason);
+
+                if ui.button("Add").clicked() {
+                    let (from, to) = (self.from.unwrap_or(today), self.to.unwrap_or(today));
+                    self.error = if to < from {
+                        Some("Time off must end on or after the day it starts".to_string())
+                    } else {
+                        availability.time_off.push(TimeOff {
+                            from,
+                            to,
+                            reason: self.reason.trim().to_string(),
+                        });
+                        availability.time_off.sort_by_key(|o| o.from);
+                        self.reason.clear();
+                        None
+                    };
+                }
+            });
+
+            if let Some(err) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+        });
+    }
+
+    fn get_display_name(&self) -> String {
+        "Availability".to_string()
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, &self.get_display_name(), self);
+    }
+}
+
+/// Hours and minutes of `time` as two drag values.
+pub(super) fn time_edit(ui: &mut egui::Ui, time: &mut NaiveTime) {
+    let mut hour = time.hour();
+    let mut minute = time.minute();
+    ui.add(DragValue::new(&mut hour).clamp_range(0..=23));
+    ui.label(":");
+    ui.add(
+        DragValue::new(&mut minute)
+            .clamp_range(0..=59)
+            .custom_formatter(|m, _| format!("{m:02}")),
+    );
+    if let Some(changed) = NaiveTime::from_hms_opt(hour, minute, 0) {
+        if (changed.hour(), changed.minute()) != (time.hour(), time.minute()) {
+            *time = changed;
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use egui::ScrollArea;
+
+use super::clients::optional_date_edit;
+use crate::domain::{Caretaker, CaretakerId, Credential, Store};
+
+/// The staff roster: who works here, what they are qualified for, and where.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Caretakers {
+    caretaker: Option<CaretakerId>,
+    search: String,
+    show_inactive: bool,
+    /// The name of the caretaker being added
+    #[serde(skip)]
+    new_name: String,
+    /// The caretaker being edited, saved back on "Save"
+    #[serde(skip)]
+    editing: Option<Caretaker>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl super::App for Caretakers {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        _user: &str,
+    ) {
+        egui::SidePanel::left("caretakers_roster")
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Search:");
+                    ui.text_edit_singleline(&mut self.search)
+                        .on_hover_text("Name or credential");
+                });
+                ui.checkbox(&mut self.show_inactive, "Show inactive");
+
+                ui.separator();
+
+                let search = self.search.to_lowercase();
+                let mut selected = None;
+                ScrollArea::vertical()
+                    .max_height(ui.available_height() - 60.0)
+                    .show(ui, |ui| {
+                        for caretaker in store
+                            .caretakers()
+                            .iter()
+                            .filter(|c| c.active || self.show_inactive)
+                            .filter(|c| {
+                                c.name.to_lowercase().contains(&search)
+                                    || c.credentials
+                                        .iter()
+                                        .any(|cred| cred.name.to_lowercase().contains(&search))
+                            })
+                        {
+                            let text = if caretaker.active {
+                                egui::RichText::new(&caretaker.name)
+                            } else {
+                                egui::RichText::new(format!("{} (inactive)", caretaker.name)).weak()
+                            };
+                            if ui
+                                .selectable_label(self.caretaker == Some(caretaker.id), text)
+                                .clicked()
+                            {
+                                selected = Some(caretaker.id);
+                            }
+                        }
+                    });
+                if let Some(id) = selected {
+                    self.select(id);
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.new_name);
+                    if ui.button("Add").clicked() {
+                        let name = self.new_name.trim();
+                        if name.is_empty() {
+                            self.error = Some("A caretaker needs a name".to_string());
+                        } else if store.caretaker_named(name).is_some() {
+                            self.error = Some(format!("{name} is already on the roster"));
+                        } else {
+                            let id = store.add_caretaker(name, "");
+                            self.new_name.clear();
+                            self.select(id);
+                            self.editing = store.caretaker(id).cloned();
+                        }
+                    }
+                });
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label("Caretakers");
+            });
+
+            ui.separator();
+
+            if let Some(err) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+
+            let Some(id) = self.caretaker else {
+                ui.label("Pick a caretaker to see their details.");
+                return;
+            };
+
+            ScrollArea::vertical().show(ui, |ui| {
+                if self.editing.is_some() {
+                    self.edit_ui(ui, store);
+                } else if let Some(caretaker) = store.caretaker(id) {
+                    let mut active = None;
+                    ui.horizontal(|ui| {
+                        if ui.button("Edit").clicked() {
+                            self.editing = Some(caretaker.clone());
+                        }
+                        if caretaker.active {
+                            if ui
+                                .button("Deactivate")
+                                .on_hover_text(
+                                    "Take them off the roster, their past tasks keep their name",
+                                )
+                                .clicked()
+                            {
+                                active = Some(false);
+                            }
+                        } else if ui.button("Reactivate").clicked() {
+                            active = Some(true);
+                        }
+                    });
+                    details_ui(ui, store, caretaker);
+
+                    if let Some(active) = active {
+                        if let Some(caretaker) = store.caretaker_mut(id) {
+                            caretaker.active = active;
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    fn get_display_name(&self) -> String {
+        "Caretakers".to_string()
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, &self.get_display_name(), self);
+    }
+}
+
+impl Caretakers {
+    fn select(&mut self, id: CaretakerId) {
+        if self.caretaker != Some(id) {
+            self.editing = None;
+        }
+        self.caretaker = Some(id);
+        self.error = None;
+    }
+
+    /// The form for the caretaker being edited.
+    fn edit_ui(&mut self, ui: &mut egui::Ui, store: &mut Store) {
+        let Some(draft) = &mut self.editing else {
+            return;
+        };
+
+        egui::Grid::new("caretaker_details").show(ui, |ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut draft.name);
+            ui.end_row();
+
+            ui.label("Home location");
+            let location_name = |id| store.location(id).map(|l| l.name.clone());
+            egui::ComboBox::from_id_source("caretaker_location")
+                .selected_text(draft.location.and_then(location_name).unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut draft.location, None, "");
+                    for location in store.locations() {
+                        ui.selectable_value(&mut draft.location, Some(location.id), &location.name);
+                    }
+                });
+            ui.end_row();
+        });
+
+        ui.separator();
+
+        ui.label("Credentials");
+        let mut remove = None;
+        egui::Grid::new("caretaker_credentials")
+            .striped(true)
+            .show(ui, |ui| {
+                for title in ["Name", "Issued", "Expires", ""] {
+                    ui.strong(title);
+                }
+                ui.end_row();
+                for (index, cred) in draft.credentials.iter_mut().enumerate() {
+                    ui.text_edit_singleline(&mut cred.name);
+                    ui.horizontal(|ui| {
+                        optional_date_edit(
+                            ui,
+                            &mut cred.issued,
+                            &format!("credential_issued_{index}"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        optional_date_edit(
+                            ui,
+                            &mut cred.expires,
+                            &format!("credential_expires_{index}"),
+                        );
+                    });
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            draft.credentials.remove(index);
+        }
+        if ui.button("Add credential").clicked() {
+            draft.credentials.push(Credential::new(""));
+        }
+
+        ui.separator();
+
+        let (mut save, mut cancel) = (false, false);
+        ui.horizontal(|ui| {
+            save = ui.button("Save").clicked();
+            cancel = ui.button("Cancel").clicked();
+        });
+        if save {
+            self.error = Self::check(store, draft).err();
+            if self.error.is_none() {
+                draft.name = draft.name.trim().to_string();
+                for cred in &mut draft.credentials {
+                    cred.name = cred.name.trim().to_string();
+                }
+                if let Some(caretaker) = store.caretaker_mut(draft.id) {
+                    *caretaker = draft.clone();
+                }
+                self.editing = None;
+            }
+        } else if cancel {
+            self.editing = None;
+            self.error = None;
+        }
+    }
+
+    /// Why the edited caretaker can't be saved, if they can't.
+    fn check(store: &Store, draft: &Caretaker) -> Result<(), String> {
+        let name = draft.name.trim();
+        if name.is_empty() {
+            return Err("A caretaker needs a name".to_string());
+        }
+        if store
+            .caretaker_named(name)
+            .map_or(false, |c| c.id != draft.id)
+        {
+            return Err(format!("{name} is already on the roster"));
+        }
+        for (index, cred) in draft.credentials.iter().enumerate() {
+            let cred = cred.name.trim();
+            if cred.is_empty() {
+                return Err("A credential needs a name".to_string());
+            }
+            if draft.credentials[..index]
+                .iter()
+                .any(|c| c.name.trim().eq_ignore_ascii_case(cred))
+            {
+                return Err(format!("{cred} is listed twice"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The read only details.
+fn details_ui(ui: &mut egui::Ui, store: &Store, caretaker: &Caretaker) {
+    ui.heading(&caretaker.name);
+    if !caretaker.active {
+        ui.weak("Inactive, they can't be assigned new tasks");
+    }
+
+    egui::Grid::new("caretaker_details").show(ui, |ui| {
+        ui.label("Home location");
+        match caretaker.location.and_then(|l| store.location(l)) {
+            Some(location) => ui.label(&location.name),
+            None => ui.weak("None"),
+        };
+        ui.end_row();
+    });
+
+    ui.separator();
+
+    ui.label("Credentials");
+    if caretaker.credentials.is_empty() {
+        ui.weak("None");
+    }
+    let today = chrono::offset::Utc::now().date_naive();
+    egui::Grid::new("caretaker_credentials")
+        .striped(true)
+        .show(ui, |ui| {
+            for cred in &caretaker.credentials {
+                ui.label(&cred.name);
+                match cred.issued {
+                    Some(issued) if issued > today => {
+                        ui.colored_label(ui.visuals().warn_fg_color, format!("From {issued}"));
+                    }
+                    Some(issued) => {
+                        ui.label(format!("Issued {issued}"));
+                    }
+                    None => {
+                        ui.label("");
+                    }
+                }
+                match cred.expires {
+                    Some(expires) if expires < today => {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("Expired {expires}"));
+                    }
+                    Some(expires) => {
+                        ui.label(format!("Expires {expires}"));
+                    }
+                    None => {
+                        ui.weak("Doesn't expire");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+
+    ui.separator();
+
+    ui.label("Tasks");
+    let mut tasks: Vec<_> = store
+        .tasks()
+        .iter()
+        .filter(|t| t.assignee == Some(caretaker.id))
+        .collect();
+    tasks.sort_by_key(|t| (t.date, t.start));
+    if tasks.is_empty() {
+        ui.weak("None");
+    }
+    egui::Grid::new("caretaker_tasks")
+        .striped(true)
+        .show(ui, |ui| {
+            for task in tasks {
+                match task.date {
+                    Some(date) => ui.label(date.to_string()),
+                    None => ui.weak("Every day"),
+                };
+                ui.label(task.time_text());
+                if let Some(client) = store.client(task.client) {
+                    ui.label(&client.name);
+                }
+                if let Some(service) = store.service(task.service) {
+                    ui.label(&service.name);
+                }
+                ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,456 @@
+use chrono::{NaiveDate, NaiveTime};
+use egui::ScrollArea;
+
+use super::availability::time_edit;
+use super::Open;
+use crate::domain::{Client, ClientId, Contact, Store};
+
+const DAYS: [&str; 7] = ["M", "T", "W", "T", "F", "S", "S"];
+
+/// Client profiles: who they are, who to call, and their care plan.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Clients {
+    client: Option<ClientId>,
+    search: String,
+    /// The name of the client being added
+    #[serde(skip)]
+    new_name: String,
+    /// The profile being edited, saved back on "Save"
+    #[serde(skip)]
+    editing: Option<Client>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl super::App for Clients {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        _user: &str,
+    ) {
+        if let Some(Open::Client(id)) = Open::take(ctx) {
+            self.select(id);
+        }
+
+        egui::SidePanel::left("clients_list")
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Search:");
+                    ui.text_edit_singleline(&mut self.search);
+                });
+
+                ui.separator();
+
+                let search = self.search.to_lowercase();
+                let mut selected = None;
+                ScrollArea::vertical()
+                    .max_height(ui.available_height() - 60.0)
+                    .show(ui, |ui| {
+                        for client in store
+                            .clients()
+                            .iter()
+                            .filter(|c| c.name.to_lowercase().contains(&search))
+                        {
+                            if ui
+                                .selectable_label(self.client == Some(client.id), &client.name)
+                                .clicked()
+                            {
+                                selected = Some(client.id);
+                            }
+                        }
+                    });
+                if let Some(id) = selected {
+                    self.select(id);
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.new_name);
+                    if ui.button("Add").clicked() {
+                        let name = self.new_name.trim();
+                        if name.is_empty() {
+                            self.error = Some("A client needs a name".to_string());
+                        } else if store.client_named(name).is_some() {
+                            self.error = Some(format!("{name} is already a client"));
+                        } else {
+                            let id = store.add_client(name);
+                            self.new_name.clear();
+                            self.select(id);
+                            self.editing = store.client(id).cloned();
+                        }
+                    }
+                });
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label("Clients");
+            });
+
+            ui.separator();
+
+            if let Some(err) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+
+            let Some(id) = self.client else {
+                ui.label("Pick a client to see their profile.");
+                return;
+            };
+
+            ScrollArea::vertical().show(ui, |ui| {
+                if self.editing.is_some() {
+                    self.edit_ui(ui, store);
+                } else if let Some(client) = store.client(id) {
+                    if ui.button("Edit").clicked() {
+                        self.editing = Some(client.clone());
+                    }
+                    profile_ui(ui, store, client);
+                }
+            });
+        });
+    }
+
+    fn get_display_name(&self) -> String {
+        "Clients".to_string()
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, &self.get_display_name(), self);
+    }
+}
+
+impl Clients {
+    fn select(&mut self, id: ClientId) {
+        if self.client != Some(id) {
+            self.editing = None;
+        }
+        self.client = Some(id);
+        self.error = None;
+    }
+
+    /// The form for the profile being edited.
+    fn edit_ui(&mut self, ui: &mut egui::Ui, store: &mut Store) {
+        let Some(draft) = &mut self.editing else {
+            return;
+        };
+
+        egui::Grid::new("client_details").show(ui, |ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut draft.name);
+            ui.end_row();
+
+            ui.label("Date of birth");
+            ui.horizontal(|ui| {
+                let mut known = draft.dob.is_some();
+                ui.checkbox(&mut known, "");
+                if !known {
+                    draft.dob = None;
+                }
+                #[cfg(feature = "chrono")]
+                if known {
+                    let dob = draft
+                        .dob
+                        .get_or_insert_with(|| chrono::offset::Utc::now().date_naive());
+                    ui.add(egui_extras::DatePickerButton::new(dob).id_source("client_dob"));
+                }
+            });
+            ui.end_row();
+
+            ui.label("Location");
+            let location_name = |id| store.location(id).map(|l| l.name.clone());
+            egui::ComboBox::from_id_source("client_location")
+                .selected_text(draft.location.and_then(location_name).unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut draft.location, None, "");
+                    for location in store.locations() {
+                        ui.selectable_value(&mut draft.location, Some(location.id), &location.name);
+                    }
+                });
+            ui.end_row();
+        });
+
+        ui.separator();
+
+        ui.label("Contacts");
+        let mut remove = None;
+        egui::Grid::new("client_contacts")
+            .striped(true)
+            .show(ui, |ui| {
+                for title in ["Name", "Relationship", "Phone", "Email", ""] {
+                    ui.strong(title);
+                }
+                ui.end_row();
+                for (index, contact) in draft.contacts.iter_mut().enumerate() {
+                    ui.text_edit_singleline(&mut contact.name);
+                    ui.text_edit_singleline(&mut contact.relationship);
+                    ui.text_edit_singleline(&mut contact.phone);
+                    ui.text_edit_singleline(&mut contact.email);
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            draft.contacts.remove(index);
+        }
+        if ui.button("Add contact").clicked() {
+            draft.contacts.push(Contact::default());
+        }
+
+        ui.separator();
+
+        ui.label("Notes");
+        ui.text_edit_multiline(&mut draft.notes);
+
+        ui.separator();
+
+        ui.label("Care plan");
+        let mut remove = None;
+        egui::Grid::new("client_care_plan")
+            .striped(true)
+            .show(ui, |ui| {
+                for (index, planned) in draft.care_plan.iter_mut().enumerate() {
+                    egui::ComboBox::from_id_source(("care_plan_service", index))
+                        .selected_text(
+                            store
+                                .service(planned.service)
+                                .map(|s| s.name.as_str())
+                                .unwrap_or_default(),
+                        )
+                        .show_ui(ui, |ui| {
+                            for service in store.services() {
+                                ui.selectable_value(
+                                    &mut planned.service,
+                                    service.id,
+                                    &service.name,
+                                );
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        for (day, on) in DAYS.iter().zip(&mut planned.days) {
+                            ui.toggle_value(on, *day);
+                        }
+                        ui.label("every");
+                        ui.add(egui::DragValue::new(&mut planned.interval).clamp_range(1..=52));
+                        ui.label("week(s)");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("From");
+                        optional_date_edit(ui, &mut planned.from, &format!("plan_from_{index}"));
+                        ui.label("until");
+                        optional_date_edit(ui, &mut planned.until, &format!("plan_until_{index}"));
+                    });
+                    ui.horizontal(|ui| {
+                        optional_time_edit(ui, &mut planned.start);
+                        ui.label("to");
+                        optional_time_edit(ui, &mut planned.end);
+                    });
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            draft.care_plan.remove(index);
+        }
+        if let Some(service) = store.services().first().map(|s| s.id) {
+            if ui.button("Add service").clicked() {
+                draft.care_plan.push(store.plan_service(service));
+            }
+        }
+
+        ui.separator();
+
+        let (mut save, mut cancel) = (false, false);
+        ui.horizontal(|ui| {
+            save = ui.button("Save").clicked();
+            cancel = ui.button("Cancel").clicked();
+        });
+        if save {
+            self.error = Self::check(store, draft).err();
+            if self.error.is_none() {
+                draft.name = draft.name.trim().to_string();
+                if let Some(client) = store.client_mut(draft.id) {
+                    *client = draft.clone();
+                }
+                self.editing = None;
+            }
+        } else if cancel {
+            self.editing = None;
+            self.error = None;
+        }
+    }
+
+    /// Why the edited profile can't be saved, if it can't.
+    fn check(store: &Store, draft: &Client) -> Result<(), String> {
+        let name = draft.name.trim();
+        if name.is_empty() {
+            return Err("A client needs a name".to_string());
+        }
+        if store.client_named(name).map_or(false, |c| c.id != draft.id) {
+            return Err(format!("{name} is already a client"));
+        }
+        for planned in &draft.care_plan {
+            if let (Some(start), Some(end)) = (planned.start, planned.end) {
+                if end <= start {
+                    return Err("A planned service ends before it starts".to_string());
+                }
+            }
+            if let (Some(from), Some(until)) = (planned.from, planned.until) {
+                if until < from {
+                    return Err("A planned service stops before it begins".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The read only profile.
+fn profile_ui(ui: &mut egui::Ui, store: &Store, client: &Client) {
+    ui.heading(&client.name);
+
+    egui::Grid::new("client_details").show(ui, |ui| {
+        ui.label("Date of birth");
+        match client.dob {
+            Some(dob) => {
+                let today = chrono::offset::Utc::now().date_naive();
+                match client.age_on(today) {
+                    Some(age) => ui.label(format!("{dob} (age {age})")),
+                    None => ui.label(dob.to_string()),
+                };
+            }
+            None => {
+                ui.weak("Unknown");
+            }
+        }
+        ui.end_row();
+
+        ui.label("Location");
+        match client.location.and_then(|l| store.location(l)) {
+            Some(location) => ui.label(&location.name),
+            None => ui.weak("None"),
+        };
+        ui.end_row();
+    });
+
+    ui.separator();
+
+    ui.label("Contacts");
+    if client.contacts.is_empty() {
+        ui.weak("None");
+    } else {
+        egui::Grid::new("client_contacts")
+            .striped(true)
+            .show(ui, |ui| {
+                for contact in &client.contacts {
+                    ui.label(&contact.name);
+                    ui.label(&contact.relationship);
+                    ui.label(&contact.phone);
+                    ui.label(&contact.email);
+                    ui.end_row();
+                }
+            });
+    }
+
+    ui.separator();
+
+    ui.label("Notes");
+    if client.notes.is_empty() {
+        ui.weak("None");
+    } else {
+        ui.label(&client.notes);
+    }
+
+    ui.separator();
+
+    ui.label("Care plan");
+    if client.care_plan.is_empty() {
+        ui.weak("None");
+    } else {
+        egui::Grid::new("client_care_plan")
+            .striped(true)
+            .show(ui, |ui| {
+                for planned in &client.care_plan {
+                    match store.service(planned.service) {
+                        Some(service) => ui.label(&service.name),
+                        None => ui.weak("Unknown service"),
+                    };
+                    ui.label(planned.rule_text());
+                    ui.label(match (planned.start, planned.end) {
+                        (Some(start), Some(end)) => {
+                            format!("{}-{}", start.format("%H:%M"), end.format("%H:%M"))
+                        }
+                        (Some(start), None) => start.format("%H:%M").to_string(),
+                        _ => String::new(),
+                    });
+                    ui.end_row();
+                }
+            });
+    }
+
+    ui.separator();
+
+    ui.label("Tasks");
+    let mut tasks: Vec<_> = store
+        .tasks()
+        .iter()
+        .filter(|t| t.client == client.id)
+        .collect();
+    tasks.sort_by_key(|t| (t.date, t.start));
+    if tasks.is_empty() {
+        ui.weak("None");
+    }
+    egui::Grid::new("client_tasks")
+        .striped(true)
+        .show(ui, |ui| {
+            for task in tasks {
+                match task.date {
+                    Some(date) => ui.label(date.to_string()),
+                    None => ui.weak("Every day"),
+                };
+                ui.label(task.time_text());
+                if let Some(service) = store.service(task.service) {
+                    ui.label(&service.name);
+                }
+                match task.assignee.and_then(|c| store.caretaker(c)) {
+                    Some(caretaker) => ui.label(&caretaker.name),
+                    None => ui.weak("Unassigned"),
+                };
+                ui.end_row();
+            }
+        });
+}
+
+/// A time that may be left unset.
+fn optional_time_edit(ui: &mut egui::Ui, time: &mut Option<NaiveTime>) {
+    let mut set = time.is_some();
+    ui.checkbox(&mut set, "");
+    match (set, time.as_mut()) {
+        (true, Some(time)) => time_edit(ui, time),
+        (true, None) => *time = NaiveTime::from_hms_opt(9, 0, 0),
+        (false, _) => *time = None,
+    }
+}
+
+/// A date that may be left unset.
+pub(super) fn optional_date_edit(ui: &mut egui::Ui, date: &mut Option<NaiveDate>, id_source: &str) {
+    let mut set = date.is_some();
+    ui.checkbox(&mut set, "");
+    if !set {
+        *date = None;
+    }
+    #[cfg(feature = "chrono")]
+    if set {
+        let date = date.get_or_insert_with(|| chrono::offset::Utc::now().date_naive());
+        ui.add(egui_extras::DatePickerButton::new(date).id_source(id_source));
+    }
+}
@@ -0,0 +1,113 @@
+use egui_extras::{Column, TableBuilder};
+
+use crate::domain::Store;
+
+/// How far ahead the dashboard can look, in days.
+const WINDOWS: [i64; 3] = [30, 60, 90];
+
+/// Credentials that have expired or are about to, so they can be renewed in
+/// time.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Credentials {
+    /// Days ahead to look
+    within: i64,
+}
+
+impl Default for Credentials {
+    fn default() -> Self {
+        Self { within: WINDOWS[0] }
+    }
+}
+
+impl super::App for Credentials {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        _user: &str,
+    ) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label("Expiring Credentials");
+            });
+
+            ui.separator();
+
+            let today = chrono::offset::Utc::now().date_naive();
+            ui.horizontal(|ui| {
+                ui.label("Expiring within:");
+                for days in WINDOWS {
+                    let count = store.expiring_credentials(today, days).len();
+                    ui.selectable_value(&mut self.within, days, format!("{days} days ({count})"));
+                }
+            });
+
+            ui.separator();
+
+            let expiring = store.expiring_credentials(today, self.within);
+            if expiring.is_empty() {
+                ui.label(format!(
+                    "No credentials expire in the next {} days.",
+                    self.within
+                ));
+                return;
+            }
+
+            TableBuilder::new(ui)
+                .striped(true)
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::remainder())
+                .header(20.0, |mut header| {
+                    for title in ["Caretaker", "Credential", "Issued", "Expires", "Days Left"] {
+                        header.col(|ui| {
+                            ui.heading(title);
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    for (caretaker, cred) in expiring {
+                        let Some(expires) = cred.expires else {
+                            continue;
+                        };
+                        body.row(24.0, |mut row| {
+                            row.col(|ui| {
+                                ui.label(&caretaker.name);
+                            });
+                            row.col(|ui| {
+                                ui.label(&cred.name);
+                            });
+                            row.col(|ui| {
+                                if let Some(issued) = cred.issued {
+                                    ui.label(issued.to_string());
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.label(expires.to_string());
+                            });
+                            row.col(|ui| {
+                                let left = (expires - today).num_days();
+                                if left < 0 {
+                                    ui.colored_label(ui.visuals().error_fg_color, "Expired");
+                                } else {
+                                    ui.colored_label(ui.visuals().warn_fg_color, left.to_string());
+                                }
+                            });
+                        });
+                    }
+                });
+        });
+    }
+
+    fn get_display_name(&self) -> String {
+        "Credentials".to_string()
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, &self.get_display_name(), self);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::domain::delivery::{Channel, Route};
+use crate::domain::{LocationId, Store};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Kind {
+    #[cfg_attr(not(target_arch = "wasm32"), default)]
+    Email,
+    Webhook,
+    File,
+    // The only one web pages can use, as they have no sockets or files
+    #[cfg_attr(target_arch = "wasm32", default)]
+    Log,
+}
+
+/// The File -> Alert delivery window, where each location's channels are set
+/// up.
+#[derive(Default)]
+pub struct DeliveryWindow {
+    open: bool,
+    /// Where the new channel applies, `None` is every location
+    location: Option<LocationId>,
+    kind: Kind,
+    server: String,
+    from: String,
+    to: String,
+    url: String,
+    path: String,
+    error: Option<String>,
+}
+
+impl DeliveryWindow {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, store: &mut Store) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Alert delivery")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("Alerts are sent to every channel of their task's location.");
+
+                ui.separator();
+
+                let mut remove = None;
+                if store.routes().is_empty() {
+                    ui.weak("No channels yet, alerts stay in the app.");
+                }
+                egui::Grid::new("routes").striped(true).show(ui, |ui| {
+                    for (index, route) in store.routes().iter().enumerate() {
+                        ui.label(location_name(store, route.location));
+                        ui.label(route.channel.to_string());
+                        if ui.small_button("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = remove {
+                    store.remove_route(index);
+                }
+
+                ui.separator();
+
+                egui::ComboBox::from_label("Location")
+                    .selected_text(location_name(store, self.location))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.location, None, "All locations");
+                        for location in store.locations() {
+                            ui.selectable_value(
+                                &mut self.location,
+                                Some(location.id),
+                                &location.name,
+                            );
+                        }
+                    });
+
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.kind, Kind::Email, "Email");
+                    ui.selectable_value(&mut self.kind, Kind::Webhook, "Webhook");
+                    ui.selectable_value(&mut self.kind, Kind::File, "File");
+                    ui.selectable_value(&mut self.kind, Kind::Log, "Log");
+                });
+                #[cfg(target_arch = "wasm32")]
+                ui.weak("Web pages can only log alerts, use the desktop app to send them.");
+
+                egui::Grid::new("channel").show(ui, |ui| match self.kind {
+                    Kind::Email => {
+                        ui.label("Mail server:");
+                        ui.text_edit_singleline(&mut self.server)
+                            .on_hover_text("host:port of an SMTP server, e.g. localhost:25");
+                        ui.end_row();
+                        ui.label("From:");
+                        ui.text_edit_singleline(&mut self.from);
+                        ui.end_row();
+                        ui.label("To:");
+                        ui.text_edit_singleline(&mut self.to)
+                            .on_hover_text("Separate addresses with commas");
+                        ui.end_row();
+                    }
+                    Kind::Webhook => {
+                        ui.label("URL:");
+                        ui.text_edit_singleline(&mut self.url)
+                            .on_hover_text("An http:// URL, alerts are POSTed as JSON");
+                        ui.end_row();
+                    }
+                    Kind::File => {
+                        ui.label("File:");
+                        ui.text_edit_singleline(&mut self.path);
+                        ui.end_row();
+                    }
+                    Kind::Log => {}
+                });
+
+                if ui.button("Add channel").clicked() {
+                    let channel = self.channel();
+                    self.error = match channel.check() {
+                        Ok(()) => {
+                            store.add_route(Route {
+                                location: self.location,
+                                channel,
+                            });
+                            None
+                        }
+                        Err(err) => Some(err),
+                    };
+                }
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            });
+        self.open = open;
+    }
+
+    fn channel(&self) -> Channel {
+        match self.kind {
+            Kind::Email => Channel::Email {
+                server: self.server.trim().to_string(),
+                from: self.from.trim().to_string(),
+                to: self
+                    .to
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|to| !to.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            },
+            Kind::Webhook => Channel::Webhook {
+                url: self.url.trim().to_string(),
+            },
+            Kind::File => Channel::File {
+                path: self.path.trim().to_string(),
+            },
+            Kind::Log => Channel::Log,
+        }
+    }
+}
+
+fn location_name(store: &Store, location: Option<LocationId>) -> String {
+    match location {
+        None => "All locations".to_string(),
+        Some(l) => store
+            .location(l)
+            .map(|l| l.name.clone())
+            .unwrap_or_else(|| "Removed location".to_string()),
+    }
+}
@@ -0,0 +1,139 @@
+use crate::domain::{export, Store, Task};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Format {
+    #[default]
+    Csv,
+    Html,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Html => "html",
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn mime(self) -> &'static str {
+        match self {
+            Format::Csv => "text/csv",
+            Format::Html => "text/html",
+        }
+    }
+}
+
+/// Saves the tasks currently shown in Assign as CSV or a printable HTML
+/// schedule. Natively it writes to a path, on the web it downloads the file.
+#[derive(Default)]
+pub struct ExportWindow {
+    open: bool,
+    format: Format,
+    #[cfg(not(target_arch = "wasm32"))]
+    path: String,
+    /// The file name `path` was last filled in with, so it follows the
+    /// format and title until the user types their own
+    #[cfg(not(target_arch = "wasm32"))]
+    default_path: String,
+    status: Option<Result<String, String>>,
+}
+
+impl ExportWindow {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.status = None;
+    }
+
+    /// `title` names the schedule and the default file name.
+    pub fn show(&mut self, ctx: &egui::Context, store: &Store, title: &str, tasks: &[&Task]) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Export").open(&mut open).show(ctx, |ui| {
+            ui.label(format!("{} task(s) in the current view", tasks.len()));
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.format, Format::Csv, "CSV");
+                ui.selectable_value(&mut self.format, Format::Html, "Printable HTML");
+            });
+
+            let file_name = format!(
+                "{}.{}",
+                title.to_lowercase().replace(' ', "-"),
+                self.format.extension()
+            );
+
+            let contents = || match self.format {
+                Format::Csv => export::csv(store, tasks.iter().copied()),
+                Format::Html => export::html(store, title, tasks.iter().copied()),
+            };
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.horizontal(|ui| {
+                if self.path.is_empty() || self.path == self.default_path {
+                    self.path = file_name.clone();
+                    self.default_path = file_name;
+                }
+                ui.label("File:");
+                ui.text_edit_singleline(&mut self.path);
+                if ui.button("Save").clicked() {
+                    let path = self.path.trim();
+                    self.status = Some(match std::fs::write(path, contents()) {
+                        Ok(()) => Ok(format!("Saved to {path}")),
+                        Err(err) => Err(format!("Could not save {path}: {err}")),
+                    });
+                }
+            });
+
+            #[cfg(target_arch = "wasm32")]
+            if ui.button("Download").clicked() {
+                self.status = Some(
+                    download(&file_name, self.format.mime(), &contents())
+                        .map(|()| format!("Downloaded {file_name}")),
+                );
+            }
+
+            match &self.status {
+                Some(Ok(msg)) => {
+                    ui.label(msg);
+                }
+                Some(Err(msg)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, msg);
+                }
+                None => {}
+            }
+        });
+        self.open = open;
+    }
+}
+
+/// Hand `contents` to the browser as a file download.
+#[cfg(target_arch = "wasm32")]
+fn download(file_name: &str, mime: &str, contents: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let err = |e: wasm_bindgen::JsValue| format!("Could not download {file_name}: {e:?}");
+
+    let parts = js_sys::Array::of1(&wasm_bindgen::JsValue::from_str(contents));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_(mime);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options).map_err(err)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(err)?;
+
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| format!("Could not download {file_name}: no document"))?;
+    let link: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(err)?
+        .dyn_into()
+        .map_err(|e| err(e.into()))?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+
+    web_sys::Url::revoke_object_url(&url).map_err(err)
+}
@@ -0,0 +1,135 @@
+use egui::{RichText, ScrollArea};
+
+use crate::domain::{import, Store};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Kind {
+    #[default]
+    Caretakers,
+    Tasks,
+}
+
+impl Kind {
+    fn columns(self) -> &'static str {
+        match self {
+            Kind::Caretakers => "name, credential",
+            Kind::Tasks => "client, task, location, service, date",
+        }
+    }
+
+    fn run(self, store: &mut Store, data: &[u8]) -> import::Report {
+        match self {
+            Kind::Caretakers => import::caretakers(store, data),
+            Kind::Tasks => import::tasks(store, data),
+        }
+    }
+}
+
+/// The File -> Import window. CSV can come from a path (native only), a file
+/// dropped on the window, or pasted text.
+#[derive(Default)]
+pub struct ImportWindow {
+    open: bool,
+    kind: Kind,
+    #[cfg(not(target_arch = "wasm32"))]
+    path: String,
+    text: String,
+    /// Where the last import came from, and how it went
+    result: Option<(String, import::Report)>,
+}
+
+impl ImportWindow {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, store: &mut Store) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Import").open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.kind, Kind::Caretakers, "Caretakers");
+                ui.selectable_value(&mut self.kind, Kind::Tasks, "Tasks");
+            });
+            ui.label(format!("Columns: {}", self.kind.columns()));
+
+            ui.separator();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                ui.text_edit_singleline(&mut self.path);
+                if ui.button("Load").clicked() {
+                    let path = self.path.trim().to_string();
+                    self.load_path(store, std::path::Path::new(&path));
+                }
+            });
+
+            ui.label("Drop a CSV file on this window, or paste it below:");
+            ui.add(
+                egui::TextEdit::multiline(&mut self.text)
+                    .code_editor()
+                    .desired_rows(6)
+                    .desired_width(f32::INFINITY),
+            );
+            if ui.button("Import pasted text").clicked() {
+                let report = self.kind.run(store, self.text.as_bytes());
+                self.result = Some(("pasted text".to_string(), report));
+            }
+
+            if let Some((source, report)) = &self.result {
+                ui.separator();
+                ui.label(format!("Imported {} row(s) from {source}", report.added));
+
+                if !report.errors.is_empty() {
+                    let color = ui.visuals().error_fg_color;
+                    ui.label(
+                        RichText::new(format!("{} row(s) skipped:", report.errors.len()))
+                            .color(color),
+                    );
+                    ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for err in &report.errors {
+                            let text = match err.line {
+                                0 => err.message.clone(),
+                                line => format!("Line {line}: {}", err.message),
+                            };
+                            ui.label(RichText::new(text).color(color));
+                        }
+                    });
+                }
+            }
+        });
+        self.open = open;
+
+        for file in ctx.input(|i| i.raw.dropped_files.clone()) {
+            if let Some(bytes) = &file.bytes {
+                let report = self.kind.run(store, bytes);
+                self.result = Some((file.name.clone(), report));
+            } else {
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(path) = &file.path {
+                    self.load_path(store, path);
+                }
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_path(&mut self, store: &mut Store, path: &std::path::Path) {
+        let source = path.display().to_string();
+        let report = match std::fs::read(path) {
+            Ok(data) => self.kind.run(store, &data),
+            Err(err) => import::Report {
+                added: 0,
+                errors: vec![import::RowError {
+                    line: 0,
+                    message: format!("could not read file: {err}"),
+                }],
+            },
+        };
+        self.result = Some((source, report));
+    }
+}
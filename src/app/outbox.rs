@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+
+use crate::domain::delivery::{Channel, Message};
+use crate::domain::{AlertId, Store};
+
+/// Sends due alert deliveries and records how they went. Sinks block, so
+/// natively they run on a worker thread; the web has no sockets or files, so
+/// only the log can be set up there, and it runs in place.
+#[derive(Default)]
+pub struct Outbox {
+    #[cfg(not(target_arch = "wasm32"))]
+    worker: Option<Worker>,
+    /// Sent to the worker and not back yet
+    in_flight: BTreeSet<(AlertId, usize)>,
+}
+
+struct Job {
+    alert: AlertId,
+    index: usize,
+    channel: Channel,
+    message: Message,
+}
+
+struct Done {
+    alert: AlertId,
+    index: usize,
+    result: Result<(), String>,
+}
+
+impl Job {
+    fn run(self) -> Done {
+        Done {
+            alert: self.alert,
+            index: self.index,
+            result: self.channel.sink().send(&self.message),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct Worker {
+    jobs: std::sync::mpsc::Sender<Job>,
+    done: std::sync::mpsc::Receiver<Done>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Worker {
+    fn start(ctx: &egui::Context) -> Self {
+        let (jobs, job_rx) = std::sync::mpsc::channel::<Job>();
+        let (done_tx, done) = std::sync::mpsc::channel();
+        let ctx = ctx.clone();
+
+        std::thread::spawn(move || {
+            for job in job_rx {
+                if done_tx.send(job.run()).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+
+        Self { jobs, done }
+    }
+}
+
+impl Outbox {
+    /// Call every frame.
+    pub fn update(&mut self, ctx: &egui::Context, store: &mut Store) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let worker = self.worker.get_or_insert_with(|| Worker::start(ctx));
+            for done in worker.done.try_iter() {
+                self.in_flight.remove(&(done.alert, done.index));
+                store.record_delivery(done.alert, done.index, done.result, Utc::now());
+            }
+        }
+
+        for (alert, index) in store.due_deliveries(Utc::now()) {
+            if self.in_flight.contains(&(alert, index)) {
+                continue;
+            }
+            let Some((channel, message)) = store.delivery_job(alert, index) else {
+                continue;
+            };
+            let job = Job {
+                alert,
+                index,
+                channel,
+                message,
+            };
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(worker) = &self.worker {
+                if worker.jobs.send(job).is_ok() {
+                    self.in_flight.insert((alert, index));
+                }
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            {
+                let done = job.run();
+                store.record_delivery(done.alert, done.index, done.result, Utc::now());
+            }
+        }
+
+        // Wake up for the next retry even if nothing else happens. Deliveries
+        // already due are in flight, and the worker repaints when they finish.
+        let now = Utc::now();
+        if let Some(next) = store.next_delivery().filter(|&next| next > now) {
+            ctx.request_repaint_after((next - now).to_std().unwrap_or_default());
+        }
+    }
+}
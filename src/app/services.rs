@@ -0,0 +1,139 @@
+use egui_extras::{Column, TableBuilder};
+
+use crate::domain::{ServiceId, Store};
+
+/// The service catalog that every task picks its service from.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Services {
+    #[serde(skip)]
+    new_name: String,
+    #[serde(skip)]
+    error: Option<String>,
+    /// The service whose required credentials are being edited, and the text
+    #[serde(skip)]
+    editing: Option<(ServiceId, String)>,
+}
+
+impl super::App for Services {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        _user: &str,
+    ) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label("Services");
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("New service:");
+                let response = ui.text_edit_singleline(&mut self.new_name);
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Add").clicked() || submitted {
+                    self.add(store);
+                }
+            });
+            if let Some(err) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+
+            ui.separator();
+
+            let mut save = None;
+
+            TableBuilder::new(ui)
+                .striped(true)
+                .column(Column::auto().resizable(true))
+                .column(Column::auto().resizable(true))
+                .column(Column::remainder())
+                .header(20.0, |mut header| {
+                    header.col(|ui| {
+                        ui.heading("Service");
+                    });
+                    header.col(|ui| {
+                        ui.heading("Required Credentials");
+                    });
+                    header.col(|ui| {
+                        ui.heading("Tasks");
+                    });
+                })
+                .body(|mut body| {
+                    for service in store.services() {
+                        let count = store
+                            .tasks()
+                            .iter()
+                            .filter(|t| t.service == service.id)
+                            .count();
+                        body.row(30.0, |mut row| {
+                            row.col(|ui| {
+                                ui.label(&service.name);
+                            });
+                            row.col(|ui| {
+                                ui.horizontal(|ui| match &mut self.editing {
+                                    Some((id, text)) if *id == service.id => {
+                                        ui.text_edit_singleline(text)
+                                            .on_hover_text("Separate credentials with commas");
+                                        if ui.button("Save").clicked() {
+                                            save = Some((*id, text.clone()));
+                                        }
+                                    }
+                                    _ => {
+                                        ui.label(service.required.join(", "));
+                                        if ui.small_button("Edit").clicked() {
+                                            self.editing =
+                                                Some((service.id, service.required.join(", ")));
+                                        }
+                                    }
+                                });
+                            });
+                            row.col(|ui| {
+                                ui.label(count.to_string());
+                            });
+                        });
+                    }
+                });
+
+            if let Some((id, text)) = save {
+                if let Some(service) = store.service_mut(id) {
+                    service.required = text
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|cred| !cred.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
+                self.editing = None;
+            }
+        });
+    }
+
+    fn get_display_name(&self) -> String {
+        "Services".to_string()
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, &self.get_display_name(), self);
+    }
+}
+
+impl Services {
+    fn add(&mut self, store: &mut Store) {
+        let name = self.new_name.trim();
+
+        self.error = if name.is_empty() {
+            Some("Enter a name for the service".to_string())
+        } else if let Some(existing) = store.service_named(name) {
+            Some(format!("{} is already in the catalog", existing.name))
+        } else {
+            store.add_service(name);
+            self.new_name.clear();
+            None
+        };
+    }
+}
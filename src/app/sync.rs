@@ -0,0 +1,549 @@
+use egui::Grid;
+
+use crate::domain::sync::{AssignmentChange, Changes, Rejection, Remote, DEFAULT_ADDRESS};
+use crate::domain::{CaretakerId, Store, Task, TaskId};
+
+/// The File -> Sync window, and the link to the sync server behind it.
+/// Requests block, so they run on a worker thread and the change feed is
+/// followed on another. The web has no sockets, so there is no sync there.
+///
+/// Assignment changes that can't reach the server are kept, in order, and
+/// sent again once the change feed gets through.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SyncWindow {
+    /// The server, e.g. `http://127.0.0.1:7878`
+    url: String,
+    /// Send assignment changes to the server as they are made, and follow
+    /// other coordinators' changes
+    share: bool,
+    #[serde(skip)]
+    open: bool,
+    #[serde(skip)]
+    worker: Option<Worker>,
+    /// Following the change feed of this URL
+    #[serde(skip)]
+    listener: Option<Listener>,
+    /// Assignment changes made while the server couldn't be reached, oldest
+    /// first
+    pending: Vec<AssignmentChange>,
+    /// Sent to the worker and not back yet
+    #[serde(skip)]
+    in_flight: usize,
+    /// Pending changes sent again and not back yet
+    #[serde(skip)]
+    replaying: usize,
+    /// Why the change feed can't be followed, cleared when it can again
+    #[serde(skip)]
+    lost: Option<String>,
+    /// How the last request went
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+    /// Changes that lost the race to another coordinator's, oldest first
+    #[serde(skip)]
+    conflicts: Vec<Conflict>,
+    /// Changes the server refused, which were undone here
+    #[serde(skip)]
+    unapplied: Vec<String>,
+}
+
+impl Default for SyncWindow {
+    fn default() -> Self {
+        Self {
+            url: format!("http://{DEFAULT_ADDRESS}"),
+            share: false,
+            open: false,
+            worker: None,
+            listener: None,
+            pending: Vec::new(),
+            in_flight: 0,
+            replaying: 0,
+            lost: None,
+            status: None,
+            conflicts: Vec::new(),
+            unapplied: Vec::new(),
+        }
+    }
+}
+
+/// An assignment the server refused because someone else got there first.
+struct Conflict {
+    /// This store's copy of the task, which now has their assignee
+    task: TaskId,
+    /// Who was dropped on it here
+    mine: Option<CaretakerId>,
+    theirs: Task,
+}
+
+enum Kind {
+    Pull,
+    Push(Box<Store>),
+    /// `replayed` if it was pending
+    Assign {
+        change: AssignmentChange,
+        replayed: bool,
+    },
+}
+
+struct Job {
+    remote: Remote,
+    kind: Kind,
+}
+
+enum Done {
+    Pulled(Result<Store, String>),
+    Pushed(Result<(), String>),
+    Assigned {
+        change: AssignmentChange,
+        replayed: bool,
+        result: Result<Task, Rejection>,
+    },
+    Changed(Result<Changes, String>),
+}
+
+impl Job {
+    fn run(self) -> Done {
+        match self.kind {
+            Kind::Pull => Done::Pulled(self.remote.pull()),
+            Kind::Push(store) => Done::Pushed(self.remote.push(&store)),
+            Kind::Assign { change, replayed } => {
+                let result = self.remote.assign(&change);
+                Done::Assigned {
+                    change,
+                    replayed,
+                    result,
+                }
+            }
+        }
+    }
+}
+
+struct Worker {
+    jobs: std::sync::mpsc::Sender<Job>,
+    done: std::sync::mpsc::Receiver<Done>,
+    /// For the listener to report on
+    done_tx: std::sync::mpsc::Sender<Done>,
+}
+
+impl Worker {
+    fn start(ctx: &egui::Context) -> Self {
+        let (jobs, job_rx) = std::sync::mpsc::channel::<Job>();
+        let (done_tx, done) = std::sync::mpsc::channel();
+        let ctx = ctx.clone();
+
+        let tx = done_tx.clone();
+        std::thread::spawn(move || {
+            for job in job_rx {
+                if tx.send(job.run()).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+
+        Self {
+            jobs,
+            done,
+            done_tx,
+        }
+    }
+}
+
+/// Follows the change feed on its own thread until dropped.
+struct Listener {
+    url: String,
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl Listener {
+    /// Seconds to wait after the server can't be reached before trying again.
+    const RETRY: u64 = 5;
+
+    fn start(ctx: &egui::Context, url: &str, done: std::sync::mpsc::Sender<Done>) -> Self {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let stop = std::sync::Arc::new(AtomicBool::new(false));
+        let remote = Remote {
+            url: url.to_string(),
+        };
+        let (ctx, stopped) = (ctx.clone(), stop.clone());
+
+        std::thread::spawn(move || {
+            // Nothing is known yet, so the first answer says to pull
+            let mut since = 0;
+            while !stopped.load(Ordering::Relaxed) {
+                let changes = remote.changes(since);
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                let failed = changes.is_err();
+                if let Ok(changes) = &changes {
+                    since = changes.seq;
+                }
+                // Even nothing new is worth passing on, as it means the
+                // server can be reached
+                if done.send(Done::Changed(changes)).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+                if failed {
+                    std::thread::sleep(std::time::Duration::from_secs(Self::RETRY));
+                }
+            }
+        });
+
+        Self {
+            url: url.to_string(),
+            stop,
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+impl SyncWindow {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// How many assignment changes are waiting for the server.
+    pub fn pending(&self) -> usize {
+        self.pending.len() + self.replaying
+    }
+
+    /// Call every frame, to share assignment changes and take in answers.
+    pub fn update(&mut self, ctx: &egui::Context, store: &mut Store) {
+        let worker = self.worker.get_or_insert_with(|| Worker::start(ctx));
+        let url = self.url.trim();
+        if !self.share {
+            self.listener = None;
+        } else if self.listener.as_ref().map_or(true, |l| l.url != url) {
+            self.listener = Some(Listener::start(ctx, url, worker.done_tx.clone()));
+        }
+
+        let done: Vec<Done> = worker.done.try_iter().collect();
+        for done in done {
+            self.finished(ctx, store, done);
+        }
+
+        for change in store.take_assignment_changes() {
+            if !self.share {
+                continue;
+            }
+            if self.pending.is_empty() {
+                let replayed = false;
+                self.send(ctx, Kind::Assign { change, replayed });
+            } else {
+                // Keep them in order behind the ones already waiting
+                self.pending.push(change);
+            }
+        }
+
+        self.conflict_ui(ctx, store);
+        self.unapplied_ui(ctx);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, store: &mut Store) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut send = None;
+        let mut replay = false;
+        egui::Window::new("Sync").open(&mut open).show(ctx, |ui| {
+            ui.label("Share the schedule with other coordinators through a zdoc-sync server.");
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Server:");
+                ui.text_edit_singleline(&mut self.url);
+            });
+            ui.checkbox(&mut self.share, "Share assignments live")
+                .on_hover_text(
+                    "Send assignment changes as they are made and show other coordinators' \
+                     as they happen. Turning it on replaces this schedule with the server's.",
+                );
+
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Pull")
+                    .on_hover_text("Replace this schedule with the server's")
+                    .clicked()
+                {
+                    send = Some(Kind::Pull);
+                }
+                if ui
+                    .button("Push")
+                    .on_hover_text(
+                        "Replace the server's schedule with this one. Refused if the server \
+                         has assignments this copy hasn't pulled.",
+                    )
+                    .clicked()
+                {
+                    send = Some(Kind::Push(Box::new(store.clone())));
+                }
+                if self.in_flight > 0 {
+                    ui.spinner();
+                }
+            });
+
+            if !self.pending.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("{} changes made offline", self.pending.len()),
+                    );
+                    if ui
+                        .button("Send now")
+                        .on_hover_text("They are sent once the server can be reached anyway")
+                        .clicked()
+                    {
+                        replay = true;
+                    }
+                });
+            }
+            if let Some(lost) = &self.lost {
+                ui.colored_label(ui.visuals().error_fg_color, format!("Offline: {lost}"));
+            }
+
+            match &self.status {
+                Some(Ok(status)) => {
+                    ui.label(status);
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                None => {}
+            }
+        });
+        self.open = open;
+
+        if replay {
+            self.replay(ctx);
+        }
+        if let Some(kind) = send {
+            self.send(ctx, kind);
+        }
+    }
+
+    /// Send the pending changes again, in the order they were made.
+    fn replay(&mut self, ctx: &egui::Context) {
+        for change in std::mem::take(&mut self.pending) {
+            self.replaying += 1;
+            let replayed = true;
+            self.send(ctx, Kind::Assign { change, replayed });
+        }
+    }
+
+    fn send(&mut self, ctx: &egui::Context, kind: Kind) {
+        let job = Job {
+            remote: Remote {
+                url: self.url.trim().to_string(),
+            },
+            kind,
+        };
+        self.in_flight += 1;
+
+        let worker = self.worker.get_or_insert_with(|| Worker::start(ctx));
+        if worker.jobs.send(job).is_err() {
+            self.in_flight -= 1;
+        }
+    }
+
+    fn finished(&mut self, ctx: &egui::Context, store: &mut Store, done: Done) {
+        if !matches!(done, Done::Changed(_)) {
+            self.in_flight -= 1;
+        }
+        let status = match done {
+            Done::Pulled(result) => result.map(|pulled| {
+                *store = pulled;
+                "Pulled the server's schedule.".to_string()
+            }),
+            Done::Pushed(result) => result.map(|()| "Pushed this schedule to the server.".into()),
+            Done::Assigned {
+                change,
+                replayed,
+                result,
+            } => {
+                if replayed {
+                    self.replaying -= 1;
+                }
+                let name = task_name(store, change.task);
+                match result {
+                    Ok(task) => {
+                        store.synced_task(&task);
+                        Ok(format!("Shared the change to {name}."))
+                    }
+                    Err(Rejection::Unreachable(err)) => {
+                        // Keep it for when the server is back
+                        self.lost = Some(err);
+                        self.pending.push(change);
+                        return;
+                    }
+                    Err(Rejection::Conflict(theirs)) => {
+                        // Show what actually happened, then ask what to do
+                        let Some(task) = store.synced_task(&theirs) else {
+                            let err = Rejection::Conflict(theirs);
+                            self.status =
+                                Some(Err(format!("Couldn't share the change to {name}: {err}")));
+                            return;
+                        };
+                        self.conflicts.push(Conflict {
+                            task,
+                            mine: change.caretaker,
+                            theirs: *theirs,
+                        });
+                        return;
+                    }
+                    Err(err) => {
+                        // Keep to what the server has rather than drift away
+                        store.revert_assignment(&change);
+                        let what = match change.caretaker {
+                            Some(_) => {
+                                format!("assigning {}", caretaker_name(store, change.caretaker))
+                            }
+                            None => "unassigning".to_string(),
+                        };
+                        let made = if replayed { " (made offline)" } else { "" };
+                        self.unapplied.push(format!("{name}, {what}{made}: {err}"));
+                        return;
+                    }
+                }
+            }
+            Done::Changed(Ok(changes)) => {
+                self.lost = None;
+                // The worker takes jobs in order, so anything pulled already
+                // has the pending changes
+                self.replay(ctx);
+                if changes.replaced {
+                    self.send(ctx, Kind::Pull);
+                }
+                for task in &changes.tasks {
+                    store.synced_task(task);
+                }
+                return;
+            }
+            Done::Changed(Err(err)) => {
+                self.lost = Some(err);
+                return;
+            }
+        };
+        self.status = Some(status);
+    }
+
+    /// Ask what to do about the oldest change that lost a race.
+    fn conflict_ui(&mut self, ctx: &egui::Context, store: &mut Store) {
+        let Some(conflict) = self.conflicts.first() else {
+            return;
+        };
+
+        let mut resolved = None;
+        egui::Window::new("Assignment conflict")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} was reassigned by someone else before your change reached the server.",
+                    task_name(store, conflict.task)
+                ));
+
+                ui.separator();
+
+                Grid::new("conflict").show(ui, |ui| {
+                    ui.label("Yours:");
+                    ui.label(caretaker_name(store, conflict.mine));
+                    ui.end_row();
+
+                    ui.label("Theirs:");
+                    let mut theirs = caretaker_name(store, conflict.theirs.assignee);
+                    if let Some(modified) = &conflict.theirs.modified {
+                        theirs +=
+                            &format!(", by {} at {}", modified.by, modified.at.format("%H:%M"));
+                    }
+                    ui.label(theirs);
+                    ui.end_row();
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Keep theirs").clicked() {
+                        resolved = Some(false);
+                    }
+                    if ui
+                        .button("Use mine")
+                        .on_hover_text("Assign it again over theirs")
+                        .clicked()
+                    {
+                        resolved = Some(true);
+                    }
+                });
+                if self.conflicts.len() > 1 {
+                    ui.weak(format!("{} more conflicts", self.conflicts.len() - 1));
+                }
+            });
+
+        let Some(use_mine) = resolved else {
+            return;
+        };
+        let conflict = self.conflicts.remove(0);
+        if use_mine {
+            // This copy is now at their version, so the change goes through
+            // unless someone beats it again
+            let result = match conflict.mine {
+                Some(caretaker) => store.assign(conflict.task, caretaker),
+                None => store.unassign(conflict.task).map(|_| ()),
+            };
+            if let Err(err) = result {
+                self.status = Some(Err(format!(
+                    "Couldn't reassign {}: {err}",
+                    task_name(store, conflict.task)
+                )));
+            }
+        }
+    }
+
+    /// List the changes the server refused.
+    fn unapplied_ui(&mut self, ctx: &egui::Context) {
+        if self.unapplied.is_empty() {
+            return;
+        }
+
+        let mut dismiss = false;
+        egui::Window::new("Changes not made")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("The server refused these changes, so they were undone here:");
+                for unapplied in &self.unapplied {
+                    ui.label(format!("• {unapplied}"));
+                }
+                ui.separator();
+                dismiss = ui.button("OK").clicked();
+            });
+        if dismiss {
+            self.unapplied.clear();
+        }
+    }
+}
+
+/// "Peter Groot - Handwashing".
+fn task_name(store: &Store, task: TaskId) -> String {
+    let Some(task) = store.task(task) else {
+        return "A removed task".to_string();
+    };
+    let client = store.client(task.client).map_or("", |c| c.name.as_str());
+    let service = store.service(task.service).map_or("", |s| s.name.as_str());
+    format!("{client} - {service}")
+}
+
+fn caretaker_name(store: &Store, caretaker: Option<CaretakerId>) -> String {
+    caretaker
+        .and_then(|c| store.caretaker(c))
+        .map_or_else(|| "Unassigned".to_string(), |c| c.name.clone())
+}
@@ -0,0 +1,443 @@
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use egui::{
+    pos2, vec2, Align, Align2, CursorIcon, FontId, Id, Label, Layout, Rect, ScrollArea, Sense,
+    Stroke, Ui,
+};
+
+use super::assign::{plan_button, plan_day, Assign};
+use super::dnd::{drag_source, drop_target};
+use crate::domain::{CaretakerId, Store, Task, TaskId};
+
+const NAME_WIDTH: f32 = 120.0;
+const LANE_HEIGHT: f32 = 32.0;
+const PX_PER_MINUTE: f32 = 1.5;
+/// Dragged times snap to this many minutes
+const SNAP: i64 = 5;
+/// How long a task without an end time is drawn
+const DEFAULT_MINUTES: i64 = 30;
+
+/// The picked day as a Gantt chart, one lane per caretaker. Blocks are dragged
+/// between lanes to reassign them, and by their edges to change their times.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Timeline {
+    date: Option<NaiveDate>,
+    #[serde(skip)]
+    resize: Option<Resize>,
+    /// The day whose planned tasks were last made
+    #[serde(skip)]
+    planned: Option<NaiveDate>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+/// An edge of a block being dragged.
+#[derive(Clone, Copy)]
+struct Resize {
+    task: TaskId,
+    edge: Edge,
+    /// How far the pointer has moved since the drag started
+    dx: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Edge {
+    Start,
+    End,
+}
+
+enum Change {
+    Reschedule(TaskId, NaiveTime, NaiveTime),
+    /// Reassign the task, `None` unassigns it
+    Move(TaskId, Option<CaretakerId>),
+}
+
+/// The day being shown.
+struct Day<'a> {
+    date: NaiveDate,
+    /// The timed tasks on `date`
+    tasks: Vec<&'a Task>,
+    first_hour: u32,
+    /// Of a lane, in points
+    width: f32,
+}
+
+/// Where times fall across a lane.
+#[derive(Clone, Copy)]
+struct Scale {
+    left: f32,
+    first_hour: u32,
+}
+
+impl Scale {
+    fn x(&self, minutes: i64) -> f32 {
+        self.left + (minutes - i64::from(self.first_hour) * 60) as f32 * PX_PER_MINUTE
+    }
+}
+
+impl super::App for Timeline {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        _frame: &mut eframe::Frame,
+        store: &mut Store,
+        _user: &str,
+    ) {
+        let mut changes = Vec::new();
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label("Timeline");
+            });
+
+            ui.separator();
+
+            let today = chrono::offset::Utc::now().date_naive();
+            let date = self.date.get_or_insert(today);
+
+            #[cfg(feature = "chrono")]
+            ui.horizontal(|ui| {
+                if ui.button("◀").on_hover_text("Previous day").clicked() {
+                    *date = date.pred_opt().unwrap_or(*date);
+                }
+                ui.add(egui_extras::DatePickerButton::new(date));
+                if ui.button("▶").on_hover_text("Next day").clicked() {
+                    *date = date.succ_opt().unwrap_or(*date);
+                }
+                if ui.button("Today").clicked() {
+                    *date = today;
+                }
+                plan_button(ui, store, *date);
+            });
+            let date = *date;
+            plan_day(store, date, &mut self.planned);
+
+            if let Some(err) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+
+            let tasks: Vec<&Task> = store.tasks().iter().filter(|t| t.is_on(date)).collect();
+            let untimed = tasks.iter().filter(|t| t.start.is_none()).count();
+            if untimed > 0 {
+                ui.weak(format!(
+                    "{untimed} task(s) without a start time aren't shown, see Assign"
+                ));
+            }
+            let tasks: Vec<&Task> = tasks.into_iter().filter(|t| t.start.is_some()).collect();
+
+            let first_hour = tasks
+                .iter()
+                .filter_map(|t| t.start)
+                .map(|s| s.hour())
+                .min()
+                .map_or(7, |h| h.min(7));
+            let last_hour = tasks
+                .iter()
+                .map(|t| (end_minutes(t) + 59) / 60)
+                .max()
+                .map_or(19, |h| (h as u32).max(19))
+                .min(24);
+            let day = Day {
+                date,
+                tasks,
+                first_hour,
+                width: (last_hour - first_hour) as f32 * 60.0 * PX_PER_MINUTE,
+            };
+
+            let dragging = day
+                .tasks
+                .iter()
+                .copied()
+                .find(|t| ui.memory(|mem| mem.is_being_dragged(block_id(t.id))));
+            let released = ui.input(|i| i.pointer.any_released());
+
+            ui.separator();
+
+            ScrollArea::both().show(ui, |ui| {
+                // Hour marks, lined up with the lanes below
+                ui.horizontal(|ui| {
+                    ui.add_sized([NAME_WIDTH, 16.0], Label::new(""));
+                    ui.add_space(4.0);
+                    let (rect, _) = ui.allocate_exact_size(vec2(day.width, 16.0), Sense::hover());
+                    let scale = Scale {
+                        left: rect.left(),
+                        first_hour,
+                    };
+                    for hour in first_hour..last_hour {
+                        ui.painter().text(
+                            pos2(scale.x(i64::from(hour) * 60), rect.center().y),
+                            Align2::LEFT_CENTER,
+                            format!("{hour:02}:00"),
+                            FontId::proportional(11.0),
+                            ui.visuals().weak_text_color(),
+                        );
+                    }
+                });
+
+                // Caretakers who have left keep a lane while they still have tasks
+                let lanes = std::iter::once(None).chain(
+                    store
+                        .caretakers()
+                        .iter()
+                        .filter(|c| c.active || day.tasks.iter().any(|t| t.assignee == Some(c.id)))
+                        .map(Some),
+                );
+                for caretaker in lanes {
+                    let lane = caretaker.map(|c| c.id);
+                    let refusal = match (dragging, caretaker) {
+                        (Some(task), Some(c)) if task.assignee != lane => {
+                            Assign::refusal(store, task, c)
+                        }
+                        _ => None,
+                    };
+
+                    ui.horizontal(|ui| {
+                        let name = caretaker.map_or("Unassigned", |c| c.name.as_str());
+                        ui.add_sized([NAME_WIDTH, LANE_HEIGHT], Label::new(name));
+
+                        let response = drop_target(ui, refusal.is_none(), |ui| {
+                            self.lane_ui(ui, store, &day, lane, &mut changes);
+                        })
+                        .response;
+
+                        if let Some(task) = dragging {
+                            if response.hovered() {
+                                match &refusal {
+                                    Some(refusal) => {
+                                        egui::show_tooltip_at_pointer(
+                                            ui.ctx(),
+                                            Id::new("refused drop"),
+                                            |ui| {
+                                                ui.label(refusal);
+                                            },
+                                        );
+                                    }
+                                    None if released && task.assignee != lane => {
+                                        changes.push(Change::Move(task.id, lane));
+                                    }
+                                    None => {}
+                                }
+                            }
+                        }
+                    });
+                }
+            });
+        });
+
+        if !changes.is_empty() {
+            // Every refusal is shown, not just the last one
+            let errors: Vec<String> = changes
+                .into_iter()
+                .filter_map(|change| {
+                    let result = match change {
+                        Change::Reschedule(task, start, end) => store.reschedule(task, start, end),
+                        Change::Move(task, Some(caretaker)) => store.assign(task, caretaker),
+                        Change::Move(task, None) => store.unassign(task).map(|_| ()),
+                    };
+                    result.err().map(|err| err.to_string())
+                })
+                .collect();
+            self.error = (!errors.is_empty()).then(|| errors.join("\n"));
+        }
+    }
+
+    fn get_display_name(&self) -> String {
+        "Timeline".to_string()
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, &self.get_display_name(), self);
+    }
+}
+
+impl Timeline {
+    /// The hours `lane` works, and a block for each of its tasks.
+    fn lane_ui(
+        &mut self,
+        ui: &mut Ui,
+        store: &Store,
+        day: &Day<'_>,
+        lane: Option<CaretakerId>,
+        changes: &mut Vec<Change>,
+    ) {
+        let (rect, _) = ui.allocate_exact_size(vec2(day.width, LANE_HEIGHT), Sense::hover());
+        let scale = Scale {
+            left: rect.left(),
+            first_hour: day.first_hour,
+        };
+
+        let painter = ui.painter();
+        let line = ui.visuals().widgets.noninteractive.bg_stroke;
+        for hour in scale.first_hour..=24 {
+            let x = scale.x(i64::from(hour) * 60);
+            if x <= rect.right() {
+                painter.vline(x, rect.y_range(), line);
+            }
+        }
+
+        // Shade the hours the caretaker is off
+        if let Some(caretaker) = lane.and_then(|c| store.caretaker(c)) {
+            let off = ui.visuals().faint_bg_color;
+            match caretaker.availability.shift_on(day.date) {
+                Ok(shift) => {
+                    let start = scale.x(minutes(shift.start)).max(rect.left());
+                    let end = scale.x(minutes(shift.end)).min(rect.right());
+                    painter.rect_filled(
+                        Rect::from_x_y_ranges(rect.left()..=start, rect.y_range()),
+                        0.0,
+                        off,
+                    );
+                    painter.rect_filled(
+                        Rect::from_x_y_ranges(end..=rect.right(), rect.y_range()),
+                        0.0,
+                        off,
+                    );
+                }
+                Err(reason) => {
+                    painter.rect_filled(rect, 0.0, off);
+                    painter.text(
+                        rect.center(),
+                        Align2::CENTER_CENTER,
+                        reason,
+                        FontId::proportional(12.0),
+                        ui.visuals().weak_text_color(),
+                    );
+                }
+            }
+        }
+
+        for task in day.tasks.iter().filter(|t| t.assignee == lane) {
+            let (start, end) = self.times(task);
+            let left = scale.x(start);
+            let right = scale.x(end).max(left + 8.0);
+            let block = Rect::from_min_max(
+                pos2(left, rect.top() + 2.0),
+                pos2(right, rect.bottom() - 2.0),
+            );
+
+            // The edges go first so they get the drag rather than the block
+            for edge in [Edge::Start, Edge::End] {
+                let x = match edge {
+                    Edge::Start => block.left(),
+                    Edge::End => block.right(),
+                };
+                let handle = Rect::from_center_size(pos2(x, block.center().y), vec2(6.0, 20.0));
+                let response = ui.interact(handle, block_id(task.id).with(edge), Sense::drag());
+                if response.hovered() || response.dragged() {
+                    ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+                }
+                if response.drag_started() {
+                    self.resize = Some(Resize {
+                        task: task.id,
+                        edge,
+                        dx: 0.0,
+                    });
+                }
+                if response.dragged() {
+                    if let Some(resize) = &mut self.resize {
+                        resize.dx += response.drag_delta().x;
+                    }
+                }
+                if response.drag_released() {
+                    let moved = self.times(task);
+                    self.resize = None;
+                    if moved != self.times(task) {
+                        changes.push(Change::Reschedule(task.id, time(moved.0), time(moved.1)));
+                    }
+                }
+            }
+
+            let service = store.service(task.service).map_or("", |s| s.name.as_str());
+            let client = store.client(task.client).map_or("", |c| c.name.as_str());
+            let text = format!("{client} - {service}");
+            let double_booked = !store.double_bookings(task).is_empty();
+
+            let mut block_ui = ui.child_ui(block, Layout::left_to_right(Align::Center));
+            drag_source(&mut block_ui, block_id(task.id), |ui| {
+                let (rect, _) = ui.allocate_exact_size(block.size(), Sense::hover());
+                let stroke = if double_booked {
+                    Stroke::new(2.0, ui.visuals().error_fg_color)
+                } else {
+                    ui.visuals().widgets.inactive.fg_stroke
+                };
+                ui.painter()
+                    .rect(rect, 4.0, ui.visuals().selection.bg_fill, stroke);
+                ui.painter().with_clip_rect(rect.shrink(2.0)).text(
+                    rect.left_center() + vec2(4.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    &text,
+                    FontId::proportional(12.0),
+                    ui.visuals().strong_text_color(),
+                );
+            });
+        }
+    }
+
+    /// The task's start and end in minutes from midnight, moved by any edge
+    /// being dragged.
+    fn times(&self, task: &Task) -> (i64, i64) {
+        let start = task.start.map_or(0, minutes);
+        let end = end_minutes(task);
+
+        let Some(resize) = self.resize.filter(|r| r.task == task.id) else {
+            return (start, end);
+        };
+        let delta = (resize.dx / PX_PER_MINUTE / SNAP as f32).round() as i64 * SNAP;
+        resized(start, end, resize.edge, delta)
+    }
+}
+
+/// `start` and `end` with `edge` moved by `delta` minutes, keeping the task
+/// at least [`SNAP`] long and within the day where there's room for that.
+fn resized(start: i64, end: i64, edge: Edge, delta: i64) -> (i64, i64) {
+    const LAST: i64 = 24 * 60 - 1;
+    match edge {
+        Edge::Start => ((start + delta).clamp(0, (end - SNAP).max(0)), end),
+        Edge::End => (start, (end + delta).clamp((start + SNAP).min(LAST), LAST)),
+    }
+}
+
+fn block_id(task: TaskId) -> Id {
+    Id::new("timeline block").with(task)
+}
+
+fn minutes(time: NaiveTime) -> i64 {
+    i64::from(time.hour() * 60 + time.minute())
+}
+
+fn end_minutes(task: &Task) -> i64 {
+    match (task.start, task.end) {
+        (_, Some(end)) => minutes(end),
+        (Some(start), None) => minutes(start) + DEFAULT_MINUTES,
+        (None, None) => 0,
+    }
+}
+
+fn time(minutes: i64) -> NaiveTime {
+    let minutes = minutes.clamp(0, 24 * 60 - 1) as u32;
+    NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0).expect("clamped to one day")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges_near_midnight_stay_within_the_day() {
+        // Ends too soon after midnight for the start to move at all
+        assert_eq!(resized(0, 3, Edge::Start, -10), (0, 3));
+        assert_eq!(resized(0, 3, Edge::Start, 10), (0, 3));
+        assert_eq!(resized(60, 120, Edge::Start, 90), (115, 120));
+
+        // Starts too late for the end to be pushed out
+        assert_eq!(
+            resized(23 * 60 + 57, 23 * 60 + 59, Edge::End, 10),
+            (1437, 1439)
+        );
+        assert_eq!(
+            resized(23 * 60 + 57, 23 * 60 + 59, Edge::End, -10),
+            (1437, 1439)
+        );
+        assert_eq!(resized(60, 120, Edge::End, -90), (60, 65));
+    }
+}
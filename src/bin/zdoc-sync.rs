@@ -0,0 +1,29 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+//! The sync server coordinators share their schedule through.
+//!
+//! `zdoc-sync [address] [database]`, listening on 127.0.0.1:7878 and keeping
+//! the data in `zdoc-sync.sqlite` unless told otherwise.
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    // Log requests if you run with `RUST_LOG=debug`.
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+    let address = args
+        .next()
+        .unwrap_or_else(|| zdoc::DEFAULT_ADDRESS.to_string());
+    let database = args
+        .next()
+        .unwrap_or_else(|| "zdoc-sync.sqlite".to_string());
+
+    if let Err(err) = zdoc::serve(&address, std::path::Path::new(&database)) {
+        eprintln!("zdoc-sync: {err}");
+        std::process::exit(1);
+    }
+}
+
+// The server needs sockets, so there is nothing to run on the web.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
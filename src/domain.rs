@@ -0,0 +1,880 @@
+//! UI independent data for zDoc.
+//!
+//! The [`Store`] owns every client, caretaker, service, location and task, and
+//! is the only place assignments are made. App screens read from it and call
+//! into it rather than keeping their own copies of the data.
+
+pub mod alert;
+pub mod auto_assign;
+pub mod availability;
+mod caretaker;
+mod catalog;
+mod client;
+pub mod delivery;
+pub mod export;
+pub mod import;
+pub mod recurrence;
+pub mod storage;
+pub mod sync;
+mod task;
+
+pub use alert::Alert;
+pub use caretaker::{Caretaker, Credential};
+pub use catalog::{Location, Service};
+pub use client::{Client, Contact, PlannedService};
+pub use task::{Modified, Task};
+
+macro_rules! id_types {
+    ($($(#[$meta:meta])* $name:ident),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            #[derive(
+                Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord,
+                serde::Deserialize, serde::Serialize,
+            )]
+            pub struct $name(u32);
+        )*
+    };
+}
+
+id_types! {
+    /// Identifies an [`Alert`] in the [`Store`].
+    AlertId,
+    /// Identifies a [`Caretaker`] in the [`Store`].
+    CaretakerId,
+    /// Identifies a [`Client`] in the [`Store`].
+    ClientId,
+    /// Identifies a [`Location`] in the [`Store`].
+    LocationId,
+    /// Identifies a [`PlannedService`] in a [`Client`]'s care plan.
+    PlanId,
+    /// Identifies a [`Service`] in the [`Store`].
+    ServiceId,
+    /// Identifies a [`Task`] in the [`Store`].
+    TaskId,
+}
+
+/// Errors returned when the [`Store`] is asked to do something inconsistent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    UnknownCaretaker(CaretakerId),
+    UnknownClient(ClientId),
+    UnknownService(ServiceId),
+    UnknownTask(TaskId),
+    UnknownAlert(AlertId),
+    /// The alert's status doesn't allow that, e.g. resolving it twice
+    AlertStatus(AlertId, alert::Status),
+    /// An alert was raised or handled without a name for its audit trail
+    Anonymous,
+    /// The caretaker lacks credentials the task's service requires
+    NotQualified {
+        caretaker: CaretakerId,
+        task: TaskId,
+        missing: Vec<String>,
+    },
+    /// The caretaker has been deactivated
+    Inactive(CaretakerId),
+    /// The task would end before it starts
+    EndsBeforeStart(TaskId),
+    /// The caretaker already has tasks at the same time
+    DoubleBooked {
+        caretaker: CaretakerId,
+        task: TaskId,
+        clashes: Vec<TaskId>,
+    },
+    /// The task's assignee was changed elsewhere since the change was made
+    Stale(TaskId),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownCaretaker(id) => write!(f, "no caretaker with id {}", id.0),
+            Error::UnknownClient(id) => write!(f, "no client with id {}", id.0),
+            Error::UnknownService(id) => write!(f, "no service with id {}", id.0),
+            Error::UnknownTask(id) => write!(f, "no task with id {}", id.0),
+            Error::UnknownAlert(id) => write!(f, "no alert with id {}", id.0),
+            Error::AlertStatus(id, status) => write!(f, "alert {} is already {status}", id.0),
+            Error::Anonymous => write!(f, "enter a name to record who did it"),
+            Error::NotQualified {
+                caretaker,
+                task,
+                missing,
+            } => write!(
+                f,
+                "caretaker {} needs {} for task {}",
+                caretaker.0,
+                missing.join(", "),
+                task.0
+            ),
+            Error::Inactive(id) => write!(f, "caretaker {} is no longer active", id.0),
+            Error::EndsBeforeStart(id) => write!(f, "task {} would end before it starts", id.0),
+            Error::Stale(id) => write!(f, "task {} was reassigned by someone else first", id.0),
+            Error::DoubleBooked {
+                caretaker,
+                task,
+                clashes,
+            } => write!(
+                f,
+                "caretaker {} is busy with task(s) {} during task {}",
+                caretaker.0,
+                clashes
+                    .iter()
+                    .map(|t| t.0.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                task.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// All of the data the app works on.
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Store {
+    next_id: u32,
+    locations: Vec<Location>,
+    services: Vec<Service>,
+    clients: Vec<Client>,
+    caretakers: Vec<Caretaker>,
+    tasks: Vec<Task>,
+    alerts: Vec<Alert>,
+    notifications: Vec<alert::Notification>,
+    routes: Vec<delivery::Route>,
+    /// Who is making changes, stamped on the tasks they assign
+    #[serde(skip)]
+    user: String,
+    /// Assignment changes not yet taken by [`Store::take_assignment_changes`]
+    #[serde(skip)]
+    changes: Vec<sync::AssignmentChange>,
+}
+
+impl Store {
+    /// A store filled with the demo data shown on first launch.
+    pub fn sample() -> Self {
+        let mut store = Self::default();
+
+        let x = store.add_location("Location X");
+        let y = store.add_location("Location Y");
+        let z = store.add_location("Location Z");
+
+        let dog = store.add_service("Dog Therapy");
+        let verbal = store.add_service("Verbal Therapy");
+        let teeth = store.add_service("Teethbrushing");
+        let stress = store.add_service("Stress Therapy");
+        let hands = store.add_service("Handwashing");
+        let meds = store.add_service("Medication");
+        store.add_service("Physical Therapy");
+        if let Some(service) = store.service_mut(meds) {
+            service.required = vec!["RN".to_string()];
+        }
+
+        let angela = store.add_client("Angela Johnson");
+        let dax = store.add_client("Dax Quil");
+        let peter = store.add_client("Peter Groot");
+        let gamora = store.add_client("Gamora Thanos");
+        for (client, location) in [(angela, x), (dax, x), (peter, y), (gamora, z)] {
+            if let Some(c) = store.client_mut(client) {
+                c.location = Some(location);
+            }
+        }
+
+        // Dog therapy is weekly, on whatever day the demo is started
+        use chrono::Datelike;
+        let today = chrono::offset::Utc::now().date_naive();
+        let mut weekly = [false; 7];
+        weekly[today.weekday().num_days_from_monday() as usize] = true;
+        let every_day = [true; 7];
+        let plans = [
+            (angela, dog, weekly, (9, 0), (10, 0)),
+            (dax, verbal, every_day, (10, 30), (11, 30)),
+            (peter, teeth, every_day, (8, 0), (8, 15)),
+            (peter, meds, every_day, (8, 30), (8, 45)),
+            (peter, dog, weekly, (9, 0), (10, 0)),
+            (peter, stress, every_day, (13, 0), (14, 0)),
+            (peter, hands, every_day, (12, 0), (12, 15)),
+            (gamora, stress, every_day, (15, 0), (16, 0)),
+        ];
+        for (client, service, days, start, end) in plans {
+            let mut planned = store.plan_service(service);
+            planned.days = days;
+            planned.start = chrono::NaiveTime::from_hms_opt(start.0, start.1, 0);
+            planned.end = chrono::NaiveTime::from_hms_opt(end.0, end.1, 0);
+            if let Some(c) = store.client_mut(client) {
+                c.care_plan.push(planned);
+            }
+        }
+        store.generate_tasks(today);
+
+        let caretakers = [
+            ("Jim Butz", "Nurse RN", x),
+            ("Dave Butz", "DSP", y),
+            ("Isaac Butz", "Developer", y),
+            ("Steve Butz", "None", z),
+        ];
+        for (name, cred, location) in caretakers {
+            let id = store.add_caretaker(name, cred);
+            if let Some(ct) = store.caretaker_mut(id) {
+                ct.location = Some(location);
+            }
+        }
+
+        store
+    }
+
+    fn next_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    pub fn locations(&self) -> &[Location] {
+        &self.locations
+    }
+
+    pub fn services(&self) -> &[Service] {
+        &self.services
+    }
+
+    pub fn clients(&self) -> &[Client] {
+        &self.clients
+    }
+
+    pub fn caretakers(&self) -> &[Caretaker] {
+        &self.caretakers
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    pub fn location(&self, id: LocationId) -> Option<&Location> {
+        self.locations.iter().find(|l| l.id == id)
+    }
+
+    pub fn service(&self, id: ServiceId) -> Option<&Service> {
+        self.services.iter().find(|s| s.id == id)
+    }
+
+    pub fn client(&self, id: ClientId) -> Option<&Client> {
+        self.clients.iter().find(|c| c.id == id)
+    }
+
+    pub fn caretaker(&self, id: CaretakerId) -> Option<&Caretaker> {
+        self.caretakers.iter().find(|c| c.id == id)
+    }
+
+    pub fn task(&self, id: TaskId) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+
+    pub fn service_mut(&mut self, id: ServiceId) -> Option<&mut Service> {
+        self.services.iter_mut().find(|s| s.id == id)
+    }
+
+    pub fn client_mut(&mut self, id: ClientId) -> Option<&mut Client> {
+        self.clients.iter_mut().find(|c| c.id == id)
+    }
+
+    pub fn caretaker_mut(&mut self, id: CaretakerId) -> Option<&mut Caretaker> {
+        self.caretakers.iter_mut().find(|c| c.id == id)
+    }
+
+    pub fn task_mut(&mut self, id: TaskId) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Look up a caretaker by name, ignoring case and surrounding whitespace.
+    pub fn caretaker_named(&self, name: &str) -> Option<&Caretaker> {
+        self.caretakers.iter().find(|c| same_name(&c.name, name))
+    }
+
+    /// The id of the location called `name`, adding it if there isn't one.
+    pub fn find_or_add_location(&mut self, name: &str) -> LocationId {
+        match self.locations.iter().find(|l| same_name(&l.name, name)) {
+            Some(l) => l.id,
+            None => self.add_location(name.trim()),
+        }
+    }
+
+    /// Look up a service in the catalog by name, ignoring case and surrounding
+    /// whitespace.
+    pub fn service_named(&self, name: &str) -> Option<&Service> {
+        self.services.iter().find(|s| same_name(&s.name, name))
+    }
+
+    /// Look up a client by name, ignoring case and surrounding whitespace.
+    pub fn client_named(&self, name: &str) -> Option<&Client> {
+        self.clients.iter().find(|c| same_name(&c.name, name))
+    }
+
+    /// The id of the client called `name`, adding them if there isn't one.
+    pub fn find_or_add_client(&mut self, name: &str) -> ClientId {
+        match self.client_named(name) {
+            Some(c) => c.id,
+            None => self.add_client(name.trim()),
+        }
+    }
+
+    pub fn add_location(&mut self, name: &str) -> LocationId {
+        let id = LocationId(self.next_id());
+        self.locations.push(Location::new(id, name));
+        id
+    }
+
+    pub fn add_service(&mut self, name: &str) -> ServiceId {
+        let id = ServiceId(self.next_id());
+        self.services.push(Service::new(id, name));
+        id
+    }
+
+    pub fn add_client(&mut self, name: &str) -> ClientId {
+        let id = ClientId(self.next_id());
+        self.clients.push(Client::new(id, name));
+        id
+    }
+
+    /// `credentials` is a comma separated list, e.g. "RN, CPR".
+    pub fn add_caretaker(&mut self, name: &str, credentials: &str) -> CaretakerId {
+        let id = CaretakerId(self.next_id());
+        self.caretakers.push(Caretaker::new(id, name, credentials));
+        id
+    }
+
+    pub fn add_task(&mut self, client: ClientId, service: ServiceId) -> Result<TaskId, Error> {
+        if self.client(client).is_none() {
+            return Err(Error::UnknownClient(client));
+        }
+        if self.service(service).is_none() {
+            return Err(Error::UnknownService(service));
+        }
+
+        let id = TaskId(self.next_id());
+        self.tasks.push(Task::new(id, client, service));
+        Ok(id)
+    }
+
+    /// The credentials `task` requires that `caretaker` doesn't hold. Empty when
+    /// they are qualified.
+    pub fn missing_credentials(&self, task: &Task, caretaker: &Caretaker) -> Vec<String> {
+        self.service(task.service)
+            .map(|s| {
+                s.required
+                    .iter()
+                    .filter(|cred| !caretaker.has_credential(cred))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The credentials `task` requires that `caretaker` holds, but that won't
+    /// be in force on the day of the task.
+    pub fn expired_credentials(&self, task: &Task, caretaker: &Caretaker) -> Vec<String> {
+        let (Some(date), Some(service)) = (task.date, self.service(task.service)) else {
+            return Vec::new();
+        };
+        service
+            .required
+            .iter()
+            .filter(|cred| caretaker.has_credential(cred))
+            .filter(|cred| {
+                !caretaker
+                    .credentials
+                    .iter()
+                    .any(|c| c.matches(cred) && c.valid_on(date))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The credentials of active caretakers that expire by `within` days after
+    /// `today`, including those that already have, soonest first.
+    pub fn expiring_credentials(
+        &self,
+        today: chrono::NaiveDate,
+        within: i64,
+    ) -> Vec<(&Caretaker, &Credential)> {
+        let last = today + chrono::Duration::days(within);
+        let mut expiring: Vec<_> = self
+            .caretakers
+            .iter()
+            .filter(|c| c.active)
+            .flat_map(|c| c.credentials.iter().map(move |cred| (c, cred)))
+            .filter(|(_, cred)| cred.expires.map_or(false, |e| e <= last))
+            .collect();
+        expiring.sort_by_key(|(c, cred)| (cred.expires, c.name.clone()));
+        expiring
+    }
+
+    /// The other tasks `caretaker` is assigned that overlap `task`.
+    pub fn clashes(&self, task: &Task, caretaker: CaretakerId) -> Vec<&Task> {
+        self.tasks
+            .iter()
+            .filter(|t| t.id != task.id && t.assignee == Some(caretaker))
+            .filter(|t| t.overlaps(task))
+            .collect()
+    }
+
+    /// The tasks that overlap `task` with the same assignee. These can only
+    /// come about by changing times after assigning, or from older data.
+    pub fn double_bookings(&self, task: &Task) -> Vec<&Task> {
+        task.assignee
+            .map(|c| self.clashes(task, c))
+            .unwrap_or_default()
+    }
+
+    /// Whether any of `caretaker`'s tasks on `date`, or on any day when `date`
+    /// is `None`, overlap.
+    pub fn is_double_booked(
+        &self,
+        caretaker: CaretakerId,
+        date: Option<chrono::NaiveDate>,
+    ) -> bool {
+        self.tasks
+            .iter()
+            .filter(|t| t.assignee == Some(caretaker))
+            .filter(|t| date.map_or(true, |d| t.is_on(d)))
+            .any(|t| !self.clashes(t, caretaker).is_empty())
+    }
+
+    /// Make `caretaker` responsible for `task`, if they are active, qualified
+    /// for it and free at the time.
+    pub fn assign(&mut self, task: TaskId, caretaker: CaretakerId) -> Result<(), Error> {
+        let t = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task)
+            .ok_or(Error::UnknownTask(task))?;
+        let ct = self
+            .caretaker(caretaker)
+            .ok_or(Error::UnknownCaretaker(caretaker))?;
+        if !ct.active {
+            return Err(Error::Inactive(caretaker));
+        }
+        let missing = self.missing_credentials(t, ct);
+        if !missing.is_empty() {
+            return Err(Error::NotQualified {
+                caretaker,
+                task,
+                missing,
+            });
+        }
+        let clashes: Vec<TaskId> = self.clashes(t, caretaker).iter().map(|t| t.id).collect();
+        if !clashes.is_empty() {
+            return Err(Error::DoubleBooked {
+                caretaker,
+                task,
+                clashes,
+            });
+        }
+
+        self.set_assignee(task, Some(caretaker));
+        Ok(())
+    }
+
+    /// Clear the assignee of `task`, returning who it was assigned to.
+    pub fn unassign(&mut self, task: TaskId) -> Result<Option<CaretakerId>, Error> {
+        let previous = self.task(task).ok_or(Error::UnknownTask(task))?.assignee;
+        if previous.is_some() {
+            self.set_assignee(task, None);
+        }
+        Ok(previous)
+    }
+
+    /// Who assignments are stamped with from now on.
+    pub fn set_user(&mut self, user: &str) {
+        if self.user != user {
+            self.user = user.to_string();
+        }
+    }
+
+    /// Record the new assignee on the task, stamped with who changed it, and
+    /// queue the change to be shared.
+    fn set_assignee(&mut self, task: TaskId, caretaker: Option<CaretakerId>) {
+        let modified = Modified {
+            by: self.user.clone(),
+            at: chrono::Utc::now(),
+        };
+        let Some(t) = self.task_mut(task) else {
+            return;
+        };
+        let change = sync::AssignmentChange {
+            task,
+            client: t.client,
+            service: t.service,
+            date: t.date,
+            occurrence: t.plan.zip(t.date),
+            version: t.version,
+            caretaker,
+            by: modified.by.clone(),
+            previous: t.assignee,
+            previous_modified: t.modified.clone(),
+        };
+        t.assignee = caretaker;
+        t.modified = Some(modified);
+        t.version += 1;
+        self.changes.push(change);
+    }
+
+    /// Move `task` to new times, as long as its assignee is free then.
+    pub fn reschedule(
+        &mut self,
+        task: TaskId,
+        start: chrono::NaiveTime,
+        end: chrono::NaiveTime,
+    ) -> Result<(), Error> {
+        if end <= start {
+            return Err(Error::EndsBeforeStart(task));
+        }
+        let mut moved = self.task(task).ok_or(Error::UnknownTask(task))?.clone();
+        moved.start = Some(start);
+        moved.end = Some(end);
+
+        if let Some(caretaker) = moved.assignee {
+            let clashes: Vec<TaskId> = self
+                .clashes(&moved, caretaker)
+                .iter()
+                .map(|t| t.id)
+                .collect();
+            if !clashes.is_empty() {
+                return Err(Error::DoubleBooked {
+                    caretaker,
+                    task,
+                    clashes,
+                });
+            }
+        }
+
+        if let Some(t) = self.task_mut(task) {
+            *t = moved;
+        }
+        Ok(())
+    }
+
+    /// How many tasks `caretaker` is assigned on `date`, or in total when `date`
+    /// is `None`. Always worked out from the tasks, so it can't drift.
+    pub fn case_count(&self, caretaker: CaretakerId, date: Option<chrono::NaiveDate>) -> usize {
+        self.tasks
+            .iter()
+            .filter(|t| t.assignee == Some(caretaker))
+            .filter(|t| date.map_or(true, |d| t.is_on(d)))
+            .count()
+    }
+
+    /// Give unassigned tasks on `to` the caretaker who had the same task on
+    /// `from`. A task matches when client, service, location and start time are
+    /// all the same. Returns how many tasks were assigned.
+    pub fn copy_assignments(&mut self, from: chrono::NaiveDate, to: chrono::NaiveDate) -> usize {
+        let key = |t: &Task| (t.client, t.service, t.location, t.start);
+
+        let mut sources: Vec<_> = self
+            .tasks
+            .iter()
+            .filter(|t| t.date == Some(from))
+            .filter_map(|t| Some((key(t), t.assignee?)))
+            .collect();
+
+        let mut copies = Vec::new();
+        for task in self.tasks.iter().filter(|t| t.date == Some(to)) {
+            if task.assignee.is_some() {
+                continue;
+            }
+            // Each of yesterday's tasks is only copied once
+            if let Some(pos) = sources.iter().position(|(k, _)| *k == key(task)) {
+                let (_, caretaker) = sources.swap_remove(pos);
+                copies.push((task.id, caretaker));
+            }
+        }
+
+        copies
+            .into_iter()
+            .filter(|&(task, caretaker)| self.assign(task, caretaker).is_ok())
+            .count()
+    }
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_task(store: &Store, id: TaskId) -> &Task {
+        store.tasks().iter().find(|t| t.id == id).unwrap()
+    }
+
+    #[test]
+    fn sample_tasks_reference_known_data() {
+        let store = Store::sample();
+
+        assert_eq!(store.tasks().len(), 8);
+        for task in store.tasks() {
+            assert!(store.client(task.client).is_some());
+            assert!(store.service(task.service).is_some());
+            assert!(task.assignee.is_none());
+        }
+    }
+
+    #[test]
+    fn ids_are_unique_across_kinds() {
+        let mut store = Store::default();
+        let client = store.add_client("Angela Johnson");
+        let service = store.add_service("Dog Therapy");
+        let task = store.add_task(client, service).unwrap();
+
+        assert_ne!(client.0, service.0);
+        assert_ne!(service.0, task.0);
+    }
+
+    #[test]
+    fn add_task_rejects_unknown_references() {
+        let mut store = Store::default();
+        let client = store.add_client("Angela Johnson");
+        let service = ServiceId(99);
+
+        assert_eq!(
+            store.add_task(client, service),
+            Err(Error::UnknownService(service))
+        );
+        assert!(store.tasks().is_empty());
+    }
+
+    #[test]
+    fn assign_sets_assignee() {
+        let mut store = Store::default();
+        let client = store.add_client("Dax Quil");
+        let service = store.add_service("Verbal Therapy");
+        let task = store.add_task(client, service).unwrap();
+        let jim = store.add_caretaker("Jim Butz", "Nurse RN");
+
+        store.assign(task, jim).unwrap();
+
+        assert_eq!(find_task(&store, task).assignee, Some(jim));
+        assert_eq!(store.case_count(jim, None), 1);
+    }
+
+    #[test]
+    fn case_counts_follow_reassign_and_unassign() {
+        let mut store = Store::sample();
+        let task = store.tasks()[0].id;
+        let jim = store.caretakers()[0].id;
+        let dave = store.caretakers()[1].id;
+
+        store.assign(task, jim).unwrap();
+        store.assign(task, dave).unwrap();
+        assert_eq!(store.case_count(jim, None), 0);
+        assert_eq!(store.case_count(dave, None), 1);
+
+        assert_eq!(store.unassign(task), Ok(Some(dave)));
+        assert_eq!(store.case_count(dave, None), 0);
+        assert_eq!(store.unassign(task), Ok(None));
+    }
+
+    #[test]
+    fn case_count_is_per_day() {
+        let mut store = Store::sample();
+        let jim = store.caretakers()[0].id;
+        let task = store.tasks()[0].id;
+        store.assign(task, jim).unwrap();
+
+        let date = find_task(&store, task).date.unwrap();
+        assert_eq!(store.case_count(jim, Some(date)), 1);
+        assert_eq!(store.case_count(jim, date.succ_opt()), 0);
+    }
+
+    #[test]
+    fn assign_unknown_caretaker_changes_nothing() {
+        let mut store = Store::sample();
+        let task = store.tasks()[0].id;
+        let nobody = CaretakerId(1000);
+
+        assert_eq!(
+            store.assign(task, nobody),
+            Err(Error::UnknownCaretaker(nobody))
+        );
+        assert!(find_task(&store, task).assignee.is_none());
+    }
+
+    #[test]
+    fn assign_requires_the_service_credentials() {
+        let mut store = Store::default();
+        let client = store.add_client("Peter Groot");
+        let meds = store.add_service("Medication");
+        store.service_mut(meds).unwrap().required = vec!["RN".to_string()];
+        let task = store.add_task(client, meds).unwrap();
+        let jim = store.add_caretaker("Jim Butz", "Nurse RN");
+        let dave = store.add_caretaker("Dave Butz", "DSP");
+
+        assert_eq!(
+            store.assign(task, dave),
+            Err(Error::NotQualified {
+                caretaker: dave,
+                task,
+                missing: vec!["RN".to_string()],
+            })
+        );
+        assert!(find_task(&store, task).assignee.is_none());
+
+        store.assign(task, jim).unwrap();
+        assert_eq!(find_task(&store, task).assignee, Some(jim));
+    }
+
+    #[test]
+    fn credentials_expire_before_the_task_date() {
+        let mut store = Store::default();
+        let client = store.add_client("Peter Groot");
+        let meds = store.add_service("Medication");
+        store.service_mut(meds).unwrap().required = vec!["RN".to_string()];
+        let task = store.add_task(client, meds).unwrap();
+        let jim = store.add_caretaker("Jim Butz", "RN, CPR");
+        let dave = store.add_caretaker("Dave Butz", "RN");
+
+        let day = |m, d| chrono::NaiveDate::from_ymd_opt(2023, m, d).unwrap();
+        store.task_mut(task).unwrap().date = Some(day(3, 15));
+        store.caretaker_mut(jim).unwrap().credentials[0].expires = Some(day(3, 14));
+        store.caretaker_mut(jim).unwrap().credentials[1].expires = Some(day(5, 1));
+        store.caretaker_mut(dave).unwrap().credentials[0].expires = Some(day(3, 15));
+
+        let t = find_task(&store, task).clone();
+        let expired = |ct| store.expired_credentials(&t, store.caretaker(ct).unwrap());
+        assert_eq!(expired(jim), vec!["RN".to_string()]);
+        assert!(expired(dave).is_empty());
+
+        let expiring: Vec<_> = store
+            .expiring_credentials(day(3, 1), 30)
+            .into_iter()
+            .map(|(c, cred)| (c.name.as_str(), cred.name.as_str()))
+            .collect();
+        assert_eq!(expiring, vec![("Jim Butz", "RN"), ("Dave Butz", "RN")]);
+        assert_eq!(store.expiring_credentials(day(3, 1), 90).len(), 3);
+    }
+
+    #[test]
+    fn deactivated_caretakers_keep_their_tasks() {
+        let mut store = Store::sample();
+        let jim = store.caretaker_named("Jim Butz").unwrap().id;
+        let dog = store.tasks()[0].id;
+        let verbal = store.tasks()[1].id;
+        store.assign(dog, jim).unwrap();
+
+        store.caretaker_mut(jim).unwrap().active = false;
+        assert_eq!(store.assign(verbal, jim), Err(Error::Inactive(jim)));
+        assert_eq!(find_task(&store, dog).assignee, Some(jim));
+        assert_eq!(store.caretaker(jim).unwrap().name, "Jim Butz");
+    }
+
+    #[test]
+    fn assign_refuses_overlapping_tasks() {
+        let mut store = Store::sample();
+        let dave = store.caretaker_named("Dave Butz").unwrap().id;
+        // Peter's 09:00-10:00 dog therapy and 08:00-08:15 teethbrushing
+        let dog = store.tasks()[4].id;
+        let teeth = store.tasks()[2].id;
+        store.assign(dog, dave).unwrap();
+        store.assign(teeth, dave).unwrap();
+
+        // Stress therapy moved to overlap the dog therapy
+        let stress = store.tasks()[5].id;
+        store.task_mut(stress).unwrap().start = chrono::NaiveTime::from_hms_opt(9, 30, 0);
+        assert_eq!(
+            store.assign(stress, dave),
+            Err(Error::DoubleBooked {
+                caretaker: dave,
+                task: stress,
+                clashes: vec![dog],
+            })
+        );
+        assert!(!store.is_double_booked(dave, None));
+
+        // Moving an assigned task can still create a clash, which is reported
+        store.task_mut(teeth).unwrap().end = chrono::NaiveTime::from_hms_opt(9, 15, 0);
+        assert!(store.is_double_booked(dave, None));
+        let clashes: Vec<TaskId> = store
+            .double_bookings(find_task(&store, dog))
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(clashes, vec![teeth]);
+    }
+
+    #[test]
+    fn reschedule_keeps_times_valid() {
+        let mut store = Store::sample();
+        let dave = store.caretaker_named("Dave Butz").unwrap().id;
+        let teeth = store.tasks()[2].id;
+        let dog = store.tasks()[4].id;
+        store.assign(teeth, dave).unwrap();
+        store.assign(dog, dave).unwrap();
+        let time = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+        assert_eq!(
+            store.reschedule(teeth, time(8, 30), time(8, 0)),
+            Err(Error::EndsBeforeStart(teeth))
+        );
+        assert_eq!(
+            store.reschedule(teeth, time(8, 30), time(9, 30)),
+            Err(Error::DoubleBooked {
+                caretaker: dave,
+                task: teeth,
+                clashes: vec![dog],
+            })
+        );
+        store.reschedule(teeth, time(8, 30), time(9, 0)).unwrap();
+        assert_eq!(find_task(&store, teeth).time_text(), "08:30-09:00");
+    }
+
+    #[test]
+    fn copy_assignments_matches_the_same_task_a_day_later() {
+        let mut store = Store::default();
+        let client = store.add_client("Peter Groot");
+        let dog = store.add_service("Dog Therapy");
+        let hands = store.add_service("Handwashing");
+        let meds = store.add_service("Medication");
+        store.add_service("Physical Therapy");
+        if let Some(service) = store.service_mut(meds) {
+            service.required = vec!["RN".to_string()];
+        }
+        let jim = store.add_caretaker("Jim Butz", "Nurse RN");
+        let dave = store.add_caretaker("Dave Butz", "DSP");
+
+        let monday = chrono::NaiveDate::from_ymd_opt(2023, 3, 6).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+        let mut add = |service, date, assignee| {
+            let id = store.add_task(client, service).unwrap();
+            store.task_mut(id).unwrap().date = Some(date);
+            if let Some(ct) = assignee {
+                store.assign(id, ct).unwrap();
+            }
+            id
+        };
+
+        add(dog, monday, Some(jim));
+        add(hands, monday, Some(dave));
+        let dog_tue = add(dog, tuesday, None);
+        let hands_tue = add(hands, tuesday, Some(jim));
+
+        assert_eq!(store.copy_assignments(monday, tuesday), 1);
+        assert_eq!(find_task(&store, dog_tue).assignee, Some(jim));
+        // Already assigned tasks are left alone
+        assert_eq!(find_task(&store, hands_tue).assignee, Some(jim));
+    }
+
+    #[test]
+    fn store_round_trips_through_serde() {
+        let mut store = Store::sample();
+        let task = store.tasks()[1].id;
+        let jim = store.caretakers()[0].id;
+        store.assign(task, jim).unwrap();
+
+        let ron = ron::to_string(&store).unwrap();
+        let restored: Store = ron::from_str(&ron).unwrap();
+
+        assert_eq!(find_task(&restored, task).assignee, Some(jim));
+        assert_eq!(restored.caretakers().len(), store.caretakers().len());
+    }
+}
@@ -0,0 +1,331 @@
+//! Alerts raised against tasks, and the notifications they send caretakers.
+//!
+//! An alert keeps its whole history, so who raised, acknowledged and resolved
+//! it, and when, is never lost.
+
+use chrono::{DateTime, Utc};
+
+use super::delivery::Delivery;
+use super::{AlertId, CaretakerId, ClientId, Error, Store, TaskId};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Urgent,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "Info"),
+            Severity::Warning => write!(f, "Warning"),
+            Severity::Urgent => write!(f, "Urgent"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Status {
+    Open,
+    Acknowledged,
+    Resolved,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Open => write!(f, "Open"),
+            Status::Acknowledged => write!(f, "Acknowledged"),
+            Status::Resolved => write!(f, "Resolved"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Action {
+    Raised,
+    Notified(CaretakerId),
+    Acknowledged,
+    Resolved,
+}
+
+/// One entry in an alert's audit trail.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Event {
+    pub at: DateTime<Utc>,
+    pub by: String,
+    pub action: Action,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Alert {
+    pub id: AlertId,
+    pub task: TaskId,
+    pub client: ClientId,
+    pub severity: Severity,
+    pub message: String,
+    /// Oldest first, always starts with [`Action::Raised`]
+    pub history: Vec<Event>,
+    /// Sending it to the channels of the task's location
+    #[serde(default)]
+    pub deliveries: Vec<Delivery>,
+}
+
+impl Alert {
+    pub fn status(&self) -> Status {
+        let mut status = Status::Open;
+        for event in &self.history {
+            match event.action {
+                Action::Acknowledged => status = Status::Acknowledged,
+                Action::Resolved => status = Status::Resolved,
+                Action::Raised | Action::Notified(_) => {}
+            }
+        }
+        status
+    }
+
+    /// Who raised it and when. Only `None` if saved data lost its history.
+    pub fn raised(&self) -> Option<&Event> {
+        self.history.first()
+    }
+}
+
+/// Tells a caretaker about an alert on one of their tasks.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Notification {
+    pub caretaker: CaretakerId,
+    pub alert: AlertId,
+    pub at: DateTime<Utc>,
+    pub read: bool,
+}
+
+impl Store {
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    pub fn alert(&self, id: AlertId) -> Option<&Alert> {
+        self.alerts.iter().find(|a| a.id == id)
+    }
+
+    /// Alerts on `task` that haven't been resolved.
+    pub fn unresolved_alerts(&self, task: TaskId) -> impl Iterator<Item = &Alert> {
+        self.alerts
+            .iter()
+            .filter(move |a| a.task == task && a.status() != Status::Resolved)
+    }
+
+    /// Raise an alert on `task`, notifying whoever it is assigned to and
+    /// queueing it for the location's delivery channels.
+    pub fn raise_alert(
+        &mut self,
+        task: TaskId,
+        severity: Severity,
+        message: &str,
+        by: &str,
+    ) -> Result<AlertId, Error> {
+        let by = by.trim();
+        if by.is_empty() {
+            return Err(Error::Anonymous);
+        }
+        let t = self.task(task).ok_or(Error::UnknownTask(task))?;
+        let (client, assignee) = (t.client, t.assignee);
+        let deliveries = self.queue_deliveries(t.location);
+
+        let now = Utc::now();
+        let event = |action| Event {
+            at: now,
+            by: by.to_string(),
+            action,
+            note: String::new(),
+        };
+
+        let id = AlertId(self.next_id());
+        let mut history = vec![event(Action::Raised)];
+        if let Some(caretaker) = assignee {
+            history.push(event(Action::Notified(caretaker)));
+            self.notifications.push(Notification {
+                caretaker,
+                alert: id,
+                at: now,
+                read: false,
+            });
+        }
+
+        self.alerts.push(Alert {
+            id,
+            task,
+            client,
+            severity,
+            message: message.to_string(),
+            history,
+            deliveries,
+        });
+
+        Ok(id)
+    }
+
+    /// Note that someone is dealing with an open alert.
+    pub fn acknowledge_alert(&mut self, id: AlertId, by: &str, note: &str) -> Result<(), Error> {
+        self.record(id, Action::Acknowledged, by, note)
+    }
+
+    /// Close an alert. Open alerts can be resolved without being acknowledged.
+    pub fn resolve_alert(&mut self, id: AlertId, by: &str, note: &str) -> Result<(), Error> {
+        self.record(id, Action::Resolved, by, note)
+    }
+
+    /// Add to an alert's audit trail, which needs to say who did it.
+    fn record(&mut self, id: AlertId, action: Action, by: &str, note: &str) -> Result<(), Error> {
+        let by = by.trim();
+        if by.is_empty() {
+            return Err(Error::Anonymous);
+        }
+        let alert = self
+            .alerts
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or(Error::UnknownAlert(id))?;
+
+        let done = matches!(
+            (&action, alert.status()),
+            (_, Status::Resolved) | (Action::Acknowledged, Status::Acknowledged)
+        );
+        if done {
+            return Err(Error::AlertStatus(id, alert.status()));
+        }
+
+        alert.history.push(Event {
+            at: Utc::now(),
+            by: by.to_string(),
+            action,
+            note: note.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Newest first.
+    pub fn notifications_for(&self, caretaker: CaretakerId) -> Vec<&Notification> {
+        let mut list: Vec<_> = self
+            .notifications
+            .iter()
+            .filter(|n| n.caretaker == caretaker)
+            .collect();
+        list.sort_by_key(|n| std::cmp::Reverse(n.at));
+        list
+    }
+
+    pub fn mark_notification_read(&mut self, caretaker: CaretakerId, alert: AlertId) {
+        for n in &mut self.notifications {
+            if n.caretaker == caretaker && n.alert == alert {
+                n.read = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_lifecycle_keeps_an_audit_trail() {
+        let mut store = Store::sample();
+        let task = store.tasks()[0].id;
+        let jim = store.caretakers()[0].id;
+        store.assign(task, jim).unwrap();
+
+        assert_eq!(
+            store.raise_alert(task, Severity::Urgent, "Refused medication", ""),
+            Err(Error::Anonymous)
+        );
+        let id = store
+            .raise_alert(task, Severity::Urgent, "Refused medication", "Ann")
+            .unwrap();
+        assert_eq!(store.alert(id).unwrap().status(), Status::Open);
+        assert_eq!(store.notifications_for(jim).len(), 1);
+
+        assert_eq!(
+            store.acknowledge_alert(id, " ", "Calling family"),
+            Err(Error::Anonymous)
+        );
+        assert_eq!(store.resolve_alert(id, "", ""), Err(Error::Anonymous));
+        store
+            .acknowledge_alert(id, "Bob", "Calling family")
+            .unwrap();
+        assert_eq!(
+            store.acknowledge_alert(id, "Bob", ""),
+            Err(Error::AlertStatus(id, Status::Acknowledged))
+        );
+        store
+            .resolve_alert(id, "Ann", "Took it after lunch")
+            .unwrap();
+        assert_eq!(
+            store.resolve_alert(id, "Ann", ""),
+            Err(Error::AlertStatus(id, Status::Resolved))
+        );
+
+        let alert = store.alert(id).unwrap();
+        let trail: Vec<_> = alert
+            .history
+            .iter()
+            .map(|e| (e.action.clone(), e.by.as_str()))
+            .collect();
+        assert_eq!(
+            trail,
+            vec![
+                (Action::Raised, "Ann"),
+                (Action::Notified(jim), "Ann"),
+                (Action::Acknowledged, "Bob"),
+                (Action::Resolved, "Ann"),
+            ]
+        );
+        assert_eq!(store.unresolved_alerts(task).count(), 0);
+    }
+
+    #[test]
+    fn alerts_saved_without_history_still_load() {
+        let mut store = Store::sample();
+        let task = store.tasks()[0].id;
+        let id = store
+            .raise_alert(task, Severity::Info, "Running late", "Ann")
+            .unwrap();
+        let mut saved = serde_json::to_value(&store).unwrap();
+        saved["alerts"][0]["history"] = serde_json::json!([]);
+
+        let store: Store = serde_json::from_value(saved).unwrap();
+        let alert = store.alert(id).unwrap();
+        assert!(alert.raised().is_none());
+        assert_eq!(alert.status(), Status::Open);
+    }
+
+    #[test]
+    fn unassigned_tasks_notify_nobody() {
+        let mut store = Store::sample();
+        let task = store.tasks()[0].id;
+
+        let id = store
+            .raise_alert(task, Severity::Info, "Running late", "Ann")
+            .unwrap();
+
+        assert_eq!(store.alert(id).unwrap().history.len(), 1);
+        assert!(store
+            .caretakers()
+            .iter()
+            .all(|c| store.notifications_for(c.id).is_empty()));
+    }
+}
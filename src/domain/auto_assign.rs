@@ -0,0 +1,161 @@
+//! Proposing caretakers for unassigned tasks.
+//!
+//! Nothing is assigned here; the proposal is shown to the coordinator, who
+//! accepts what they like through [`Store::assign`].
+
+use super::{CaretakerId, Store, Task, TaskId};
+
+/// Who auto-assign would give a task to, `None` when nobody fits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Proposal {
+    pub task: TaskId,
+    pub caretaker: Option<CaretakerId>,
+}
+
+impl Store {
+    /// Propose a caretaker for each unassigned task in `tasks`.
+    ///
+    /// A caretaker fits a task when they hold its credentials, work at its
+    /// location, are on shift and aren't busy with another task at the same
+    /// time. Of those, the one with the fewest tasks that day gets it,
+    /// counting what has been proposed so far. The tasks with the fewest
+    /// candidates are placed first, so they aren't left with nobody.
+    pub fn propose_assignments<'a>(
+        &self,
+        tasks: impl IntoIterator<Item = &'a Task>,
+    ) -> Vec<Proposal> {
+        let mut open: Vec<(&Task, Vec<CaretakerId>)> = tasks
+            .into_iter()
+            .filter(|t| t.assignee.is_none())
+            .map(|t| (t, self.candidates(t)))
+            .collect();
+        open.sort_by_key(|(t, candidates)| (candidates.len(), t.date, t.start));
+
+        // Every task each caretaker would end up with
+        let mut planned: Vec<(CaretakerId, &Task)> = self
+            .tasks
+            .iter()
+            .filter_map(|t| Some((t.assignee?, t)))
+            .collect();
+
+        let mut proposals = Vec::new();
+        for (task, candidates) in open {
+            let load = |c: CaretakerId| {
+                planned
+                    .iter()
+                    .filter(|(p, t)| *p == c && task.date.map_or(true, |d| t.is_on(d)))
+                    .count()
+            };
+            let free = |c: CaretakerId| !planned.iter().any(|(p, t)| *p == c && t.overlaps(task));
+            let best = candidates
+                .into_iter()
+                .filter(|&c| free(c))
+                .min_by_key(|&c| (load(c), self.caretaker(c).map(|c| &c.name)));
+
+            if let Some(c) = best {
+                planned.push((c, task));
+            }
+            proposals.push(Proposal {
+                task: task.id,
+                caretaker: best,
+            });
+        }
+
+        proposals
+    }
+
+    /// The active caretakers qualified for `task`, with credentials in force on
+    /// the day, and working where and when it takes place.
+    fn candidates(&self, task: &Task) -> Vec<CaretakerId> {
+        self.caretakers
+            .iter()
+            .filter(|c| c.active)
+            .filter(|c| self.missing_credentials(task, c).is_empty())
+            .filter(|c| self.expired_credentials(task, c).is_empty())
+            .filter(|c| c.availability.can_work(task))
+            .filter(|c| {
+                task.location.is_none() || c.location.is_none() || c.location == task.location
+            })
+            .map(|c| c.id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposed<'a>(store: &'a Store, proposals: &[Proposal]) -> Vec<(&'a str, &'a str)> {
+        proposals
+            .iter()
+            .map(|p| {
+                let task = store.task(p.task).unwrap();
+                let service = store.service(task.service).unwrap();
+                let caretaker = p
+                    .caretaker
+                    .and_then(|c| store.caretaker(c))
+                    .map_or("", |c| c.name.as_str());
+                (service.name.as_str(), caretaker)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn spreads_location_y_between_its_caretakers() {
+        let store = Store::sample();
+        let y = store.locations()[1].id;
+        let tasks: Vec<&Task> = store
+            .tasks()
+            .iter()
+            .filter(|t| t.location == Some(y))
+            .collect();
+
+        let proposals = store.propose_assignments(tasks);
+        let mut names = proposed(&store, &proposals);
+        names.sort();
+
+        // Nobody at Y holds RN, Dave and Isaac share the rest
+        assert_eq!(
+            names,
+            vec![
+                ("Dog Therapy", "Isaac Butz"),
+                ("Handwashing", "Dave Butz"),
+                ("Medication", ""),
+                ("Stress Therapy", "Isaac Butz"),
+                ("Teethbrushing", "Dave Butz"),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_assigned_tasks_and_busy_caretakers() {
+        let mut store = Store::sample();
+        let x = store.locations()[0].id;
+        let jim = store.caretaker_named("Jim Butz").unwrap().id;
+        let dog = store.tasks()[0].id;
+        store.assign(dog, jim).unwrap();
+
+        // Same time as the assigned dog therapy
+        let client = store.find_or_add_client("Dax Quil");
+        let hands = store.service_named("Handwashing").unwrap().id;
+        let clash = store.add_task(client, hands).unwrap();
+        let first = store.task(dog).unwrap().clone();
+        let t = store.task_mut(clash).unwrap();
+        t.location = Some(x);
+        t.date = first.date;
+        t.start = first.start;
+        t.end = first.end;
+
+        let tasks: Vec<&Task> = store
+            .tasks()
+            .iter()
+            .filter(|t| t.location == Some(x))
+            .collect();
+        let proposals = store.propose_assignments(tasks);
+
+        assert_eq!(
+            proposed(&store, &proposals),
+            vec![("Handwashing", ""), ("Verbal Therapy", "Jim Butz")]
+        );
+    }
+}
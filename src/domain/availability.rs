@@ -0,0 +1,160 @@
+//! When caretakers work: regular weekly hours and time off.
+
+use chrono::{Datelike, NaiveDate, NaiveTime};
+
+use super::Task;
+
+/// The hours worked on one day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Shift {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Shift {
+    pub fn all_day() -> Self {
+        Self {
+            start: NaiveTime::MIN,
+            end: NaiveTime::from_hms_opt(23, 59, 59).expect("valid time"),
+        }
+    }
+
+    /// Whether the whole task fits in the shift. Untimed tasks always fit.
+    fn fits(&self, task: &Task) -> bool {
+        let start = task.start.map_or(true, |s| self.start <= s && s < self.end);
+        let end = task.end.map_or(true, |e| self.start < e && e <= self.end);
+        start && end
+    }
+}
+
+impl std::fmt::Display for Shift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if *self == Shift::all_day() {
+            write!(f, "All day")
+        } else {
+            write!(
+                f,
+                "{}-{}",
+                self.start.format("%H:%M"),
+                self.end.format("%H:%M")
+            )
+        }
+    }
+}
+
+/// Days off outside the weekly pattern, e.g. PTO, first and last day included.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct TimeOff {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Availability {
+    /// Hours worked each weekday, Monday first. `None` is a day off.
+    pub week: [Option<Shift>; 7],
+    pub time_off: Vec<TimeOff>,
+}
+
+impl Default for Availability {
+    /// Available all the time, so nobody disappears until their hours are set.
+    fn default() -> Self {
+        Self {
+            week: [Some(Shift::all_day()); 7],
+            time_off: Vec::new(),
+        }
+    }
+}
+
+impl Availability {
+    /// The hours worked on `date`, or why they aren't working.
+    pub fn shift_on(&self, date: NaiveDate) -> Result<Shift, String> {
+        if let Some(off) = self
+            .time_off
+            .iter()
+            .find(|o| o.from <= date && date <= o.to)
+        {
+            return Err(if off.reason.is_empty() {
+                "Time off".to_string()
+            } else {
+                off.reason.clone()
+            });
+        }
+
+        self.week[date.weekday().num_days_from_monday() as usize]
+            .ok_or_else(|| "Day off".to_string())
+    }
+
+    /// Whether they work when `task` takes place. Undated tasks could be any
+    /// day, so they are always allowed.
+    pub fn can_work(&self, task: &Task) -> bool {
+        match task.date {
+            Some(date) => self.shift_on(date).map_or(false, |shift| shift.fits(task)),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn weekends_and_time_off_are_unavailable() {
+        let mut availability = Availability::default();
+        let nine_to_five = Shift {
+            start: time(9, 0),
+            end: time(17, 0),
+        };
+        availability.week = [Some(nine_to_five); 7];
+        availability.week[5] = None;
+        availability.week[6] = None;
+        availability.time_off.push(TimeOff {
+            from: NaiveDate::from_ymd_opt(2023, 3, 7).unwrap(),
+            to: NaiveDate::from_ymd_opt(2023, 3, 8).unwrap(),
+            reason: "Vacation".to_string(),
+        });
+
+        // 2023-03-06 is a Monday
+        let day = |d| NaiveDate::from_ymd_opt(2023, 3, d).unwrap();
+        assert_eq!(availability.shift_on(day(6)), Ok(nine_to_five));
+        assert_eq!(availability.shift_on(day(8)), Err("Vacation".to_string()));
+        assert_eq!(availability.shift_on(day(9)), Ok(nine_to_five));
+        assert_eq!(availability.shift_on(day(11)), Err("Day off".to_string()));
+    }
+
+    #[test]
+    fn tasks_must_fit_in_the_shift() {
+        let mut store = super::super::Store::sample();
+        let mut task = store.tasks()[0].clone();
+        let availability = Availability {
+            week: [Some(Shift {
+                start: time(9, 0),
+                end: time(12, 0),
+            }); 7],
+            ..Availability::default()
+        };
+
+        task.start = Some(time(9, 0));
+        task.end = Some(time(10, 0));
+        assert!(availability.can_work(&task));
+        task.end = Some(time(12, 30));
+        assert!(!availability.can_work(&task));
+        task.start = Some(time(8, 30));
+        task.end = None;
+        assert!(!availability.can_work(&task));
+
+        // Auto-assign leaves out whoever is off
+        let jim = store.caretaker_named("Jim Butz").unwrap().id;
+        let first = store.tasks()[0].clone();
+        store.caretaker_mut(jim).unwrap().availability.week = [None; 7];
+        let proposals = store.propose_assignments([&first]);
+        assert_eq!(proposals[0].caretaker, None);
+    }
+}
@@ -0,0 +1,176 @@
+use super::availability::Availability;
+use super::{CaretakerId, LocationId};
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Caretaker {
+    pub id: CaretakerId,
+    pub name: String,
+    /// Older data saved these as one string, e.g. "Nurse RN"
+    #[serde(
+        alias = "cred",
+        default,
+        deserialize_with = "Credential::deserialize_list"
+    )]
+    pub credentials: Vec<Credential>,
+    /// Where they usually work
+    #[serde(default)]
+    pub location: Option<LocationId>,
+    #[serde(default)]
+    pub availability: Availability,
+    /// Whether they are still on staff. Caretakers who leave are deactivated
+    /// rather than removed, so the tasks they were assigned keep their name.
+    #[serde(default = "Caretaker::default_active")]
+    pub active: bool,
+}
+
+impl Caretaker {
+    /// `credentials` is a comma separated list, e.g. "RN, CPR".
+    pub(super) fn new(id: CaretakerId, name: &str, credentials: &str) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            credentials: Credential::parse_list(credentials),
+            location: None,
+            availability: Availability::default(),
+            active: true,
+        }
+    }
+
+    fn default_active() -> bool {
+        true
+    }
+
+    /// Whether they hold `cred` by its whole name, so "Med Tech" doesn't count
+    /// as "Tech". Case and extra spaces are ignored.
+    pub fn has_credential(&self, cred: &str) -> bool {
+        self.credentials.iter().any(|c| c.matches(cred))
+    }
+
+    /// Their credentials that aren't in force on `date`.
+    pub fn lapsed_on(&self, date: chrono::NaiveDate) -> Vec<&Credential> {
+        self.credentials
+            .iter()
+            .filter(|c| !c.valid_on(date))
+            .collect()
+    }
+
+    /// "RN, CPR", or "None".
+    pub fn credentials_text(&self) -> String {
+        if self.credentials.is_empty() {
+            "None".to_string()
+        } else {
+            self.credentials
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    }
+}
+
+impl std::fmt::Display for Caretaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A qualification held by a caretaker, e.g. "RN".
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Credential {
+    pub name: String,
+    #[serde(default)]
+    pub issued: Option<chrono::NaiveDate>,
+    /// The last day it is valid, if it lapses
+    #[serde(default)]
+    pub expires: Option<chrono::NaiveDate>,
+}
+
+impl Credential {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            issued: None,
+            expires: None,
+        }
+    }
+
+    /// Whether it is in force on `date`: issued by then and not yet expired.
+    pub fn valid_on(&self, date: chrono::NaiveDate) -> bool {
+        self.issued.map_or(true, |i| i <= date) && self.expires.map_or(true, |e| date <= e)
+    }
+
+    /// Whether `cred` is its whole name. Case and extra spaces are ignored.
+    pub(super) fn matches(&self, cred: &str) -> bool {
+        let words = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+        let (name, cred) = (words(&self.name), words(cred));
+        !cred.is_empty() && name.eq_ignore_ascii_case(&cred)
+    }
+
+    /// Credentials from a comma separated list. "None" and blanks are left out.
+    /// Older data kept them as free text with a space between each, e.g.
+    /// "Nurse RN", so a list without commas is split into words.
+    fn parse_list(list: &str) -> Vec<Self> {
+        let names: Vec<&str> = if list.contains(',') {
+            list.split(',').collect()
+        } else {
+            list.split_whitespace().collect()
+        };
+        names
+            .into_iter()
+            .map(str::trim)
+            .filter(|c| !c.is_empty() && !c.eq_ignore_ascii_case("none"))
+            .map(Self::new)
+            .collect()
+    }
+
+    /// Accept both a list and the single string older data holds.
+    fn deserialize_list<'de, D>(deserializer: D) -> Result<Vec<Self>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Saved {
+            List(Vec<Credential>),
+            Text(String),
+        }
+
+        Ok(match serde::Deserialize::deserialize(deserializer)? {
+            Saved::List(list) => list,
+            Saved::Text(text) => Self::parse_list(&text),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_credentials_saved_as_text() {
+        let old = r#"(id: (7), name: "Jim Butz", cred: "Nurse RN")"#;
+        let jim: Caretaker = ron::from_str(old).unwrap();
+        assert_eq!(
+            jim.credentials,
+            vec![Credential::new("Nurse"), Credential::new("RN")]
+        );
+        assert!(jim.has_credential("rn"));
+        assert!(jim.active);
+
+        let none: Caretaker = ron::from_str(r#"(id: (8), name: "Steve", cred: "None")"#).unwrap();
+        assert!(none.credentials.is_empty());
+        assert_eq!(none.credentials_text(), "None");
+
+        let tech: Caretaker =
+            ron::from_str(r#"(id: (9), name: "Ann", cred: "Med  Tech, CPR")"#).unwrap();
+        assert!(tech.has_credential("med tech"));
+        assert!(tech.has_credential(" Med Tech "));
+        assert!(!tech.has_credential("Tech"));
+        assert!(!tech.has_credential("Tech Med"));
+        assert!(!tech.has_credential(""));
+
+        let saved = ron::to_string(&jim).unwrap();
+        let restored: Caretaker = ron::from_str(&saved).unwrap();
+        assert_eq!(restored.credentials, jim.credentials);
+    }
+}
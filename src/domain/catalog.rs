@@ -0,0 +1,37 @@
+use super::{LocationId, ServiceId};
+
+/// A site where care is given.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Location {
+    pub id: LocationId,
+    pub name: String,
+}
+
+impl Location {
+    pub(super) fn new(id: LocationId, name: &str) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// A kind of care a task can provide, e.g. "Dog Therapy".
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Service {
+    pub id: ServiceId,
+    pub name: String,
+    /// Credentials a caretaker must hold to be given this service, e.g. "RN"
+    #[serde(default)]
+    pub required: Vec<String>,
+}
+
+impl Service {
+    pub(super) fn new(id: ServiceId, name: &str) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            required: Vec::new(),
+        }
+    }
+}
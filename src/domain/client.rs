@@ -0,0 +1,177 @@
+use chrono::{Datelike, NaiveDate};
+
+use super::{ClientId, LocationId, PlanId, ServiceId};
+
+/// Someone receiving care.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Client {
+    pub id: ClientId,
+    pub name: String,
+    #[serde(default)]
+    pub dob: Option<NaiveDate>,
+    /// Where they receive care
+    #[serde(default)]
+    pub location: Option<LocationId>,
+    #[serde(default)]
+    pub contacts: Vec<Contact>,
+    #[serde(default)]
+    pub notes: String,
+    /// The services they receive regularly
+    #[serde(default)]
+    pub care_plan: Vec<PlannedService>,
+}
+
+impl Client {
+    pub(super) fn new(id: ClientId, name: &str) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            dob: None,
+            location: None,
+            contacts: Vec::new(),
+            notes: String::new(),
+            care_plan: Vec::new(),
+        }
+    }
+
+    /// Age in whole years on `date`.
+    pub fn age_on(&self, date: NaiveDate) -> Option<u32> {
+        date.years_since(self.dob?)
+    }
+}
+
+/// Family, a guardian, a doctor, anyone to call about the client.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Contact {
+    pub name: String,
+    /// e.g. "Daughter", "GP"
+    pub relationship: String,
+    pub phone: String,
+    pub email: String,
+}
+
+/// A service in a care plan, given on the same days every week or every few
+/// weeks. Tasks are made from it with [`Store::generate_tasks`].
+///
+/// [`Store::generate_tasks`]: super::Store::generate_tasks
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct PlannedService {
+    pub id: PlanId,
+    pub service: ServiceId,
+    /// Monday first
+    pub days: [bool; 7],
+    /// Every how many weeks, counted from the week of `from`
+    #[serde(default = "PlannedService::every_week")]
+    pub interval: u32,
+    /// The first and last days it is given, both optional
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+    pub start: Option<chrono::NaiveTime>,
+    pub end: Option<chrono::NaiveTime>,
+}
+
+impl PlannedService {
+    /// Every day, from now on.
+    pub(super) fn new(id: PlanId, service: ServiceId) -> Self {
+        Self {
+            id,
+            service,
+            days: [true; 7],
+            interval: Self::every_week(),
+            from: None,
+            until: None,
+            start: None,
+            end: None,
+        }
+    }
+
+    fn every_week() -> u32 {
+        1
+    }
+
+    /// Whether it is given on `date`.
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        if self.from.map_or(false, |from| date < from) || self.until.map_or(false, |u| date > u) {
+            return false;
+        }
+        if !self.days[date.weekday().num_days_from_monday() as usize] {
+            return false;
+        }
+
+        // Weeks are counted Monday to Sunday, day 1 of the common era was a Monday
+        let week = |d: NaiveDate| (d.num_days_from_ce() - 1).div_euclid(7);
+        let weeks = week(date) - self.from.map_or(0, week);
+        weeks.rem_euclid(self.interval.max(1) as i32) == 0
+    }
+
+    /// "Every day", "Weekdays" or the days, e.g. "Mon, Wed, Fri", then how
+    /// often and between which dates, e.g. "Mon, every 2 weeks, until
+    /// 2023-06-30".
+    pub fn rule_text(&self) -> String {
+        const NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+        let mut text = match self.days {
+            [true, true, true, true, true, true, true] => "Every day".to_string(),
+            [true, true, true, true, true, false, false] => "Weekdays".to_string(),
+            days => NAMES
+                .iter()
+                .zip(days)
+                .filter(|(_, on)| *on)
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        if self.interval > 1 {
+            text += &format!(", every {} weeks", self.interval);
+        }
+        if let Some(from) = self.from {
+            text += &format!(", from {from}");
+        }
+        if let Some(until) = self.until {
+            text += &format!(", until {until}");
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_plan_days_and_age() {
+        let mut client = Client::new(ClientId(1), "Peter Groot");
+        let mut plan = PlannedService::new(PlanId(3), ServiceId(2));
+        assert_eq!(plan.rule_text(), "Every day");
+        plan.days = [true, false, true, false, true, false, false];
+        assert_eq!(plan.rule_text(), "Mon, Wed, Fri");
+
+        client.dob = NaiveDate::from_ymd_opt(1950, 6, 15);
+        let day = |m, d| NaiveDate::from_ymd_opt(2023, m, d).unwrap();
+        assert_eq!(client.age_on(day(6, 14)), Some(72));
+        assert_eq!(client.age_on(day(6, 15)), Some(73));
+    }
+
+    #[test]
+    fn plans_repeat_every_few_weeks_between_dates() {
+        // 2023-03-06 is a Monday
+        let day = |d| NaiveDate::from_ymd_opt(2023, 3, d).unwrap();
+        let mut plan = PlannedService::new(PlanId(3), ServiceId(2));
+        plan.days = [false, false, true, false, false, false, false];
+        assert!(plan.occurs_on(day(8)));
+        assert!(!plan.occurs_on(day(9)));
+        assert!(plan.occurs_on(day(15)));
+
+        plan.interval = 2;
+        plan.from = Some(day(6));
+        plan.until = Some(day(31));
+        assert_eq!(
+            plan.rule_text(),
+            "Wed, every 2 weeks, from 2023-03-06, until 2023-03-31"
+        );
+        let days: Vec<_> = (1..=31).filter(|&d| plan.occurs_on(day(d))).collect();
+        assert_eq!(days, vec![8, 22]);
+    }
+}
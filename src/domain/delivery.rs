@@ -0,0 +1,757 @@
+//! Sending alerts out of the app, by email, webhook or to a file.
+//!
+//! Each location has its own list of [`Channel`]s. Raising an alert queues a
+//! [`Delivery`] per channel, which is retried with a growing delay until it is
+//! sent or [`MAX_ATTEMPTS`] is reached. The [`Store`] only records what should
+//! be sent and how it went; the caller runs the [`Sink`]s, which block.
+//!
+//! Email and webhooks speak plain SMTP and HTTP. For TLS, point them at a
+//! local relay.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::alert::Severity;
+use super::{AlertId, LocationId, Store};
+
+/// Deliveries give up after this many failed attempts.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// Seconds to wait before the first retry, doubled after each failure.
+const RETRY_DELAY: i64 = 30;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Somewhere alerts can be sent.
+pub trait Sink {
+    fn send(&self, message: &Message) -> Result<(), String>;
+}
+
+/// How to reach a sink, as configured by the user.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Channel {
+    /// Mail `to` through the SMTP server at `server`, a `host:port`
+    Email {
+        server: String,
+        from: String,
+        to: Vec<String>,
+    },
+    /// POST the alert as JSON to an `http://` URL
+    Webhook { url: String },
+    /// Append a line per alert to a file
+    File { path: String },
+    /// Write the alert to the app's log
+    Log,
+}
+
+impl Channel {
+    pub fn sink(&self) -> Box<dyn Sink + Send> {
+        match self {
+            Channel::Email { server, from, to } => Box::new(SmtpSink {
+                server: server.clone(),
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            Channel::Webhook { url } => Box::new(WebhookSink { url: url.clone() }),
+            Channel::File { path } => Box::new(FileSink { path: path.clone() }),
+            Channel::Log => Box::new(LogSink),
+        }
+    }
+
+    /// Problems that would stop anything being sent, so they can be fixed
+    /// before the channel is saved.
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            Channel::Email { server, from, to } => {
+                if !server.contains(':') {
+                    Err("the mail server needs a port, e.g. localhost:25".to_string())
+                } else if !is_address(from) {
+                    Err(format!("\"{from}\" is not an email address"))
+                } else if to.is_empty() {
+                    Err("add at least one recipient".to_string())
+                } else if let Some(bad) = to.iter().find(|to| !is_address(to)) {
+                    Err(format!("\"{bad}\" is not an email address"))
+                } else {
+                    Ok(())
+                }
+            }
+            Channel::Webhook { url } => split_url(url).map(|_| ()),
+            Channel::File { path } if path.trim().is_empty() => {
+                Err("enter a file to write to".to_string())
+            }
+            Channel::File { .. } | Channel::Log => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Channel::Email { server, to, .. } => write!(f, "Email {} via {server}", to.join(", ")),
+            Channel::Webhook { url } => write!(f, "Webhook {url}"),
+            Channel::File { path } => write!(f, "File {path}"),
+            Channel::Log => write!(f, "Log"),
+        }
+    }
+}
+
+/// Alerts at `location` go to `channel`. `None` means every location.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Route {
+    pub location: Option<LocationId>,
+    pub channel: Channel,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Status {
+    /// Waiting to be sent, or to be retried
+    Pending,
+    Sent(DateTime<Utc>),
+    /// Out of attempts
+    Failed,
+}
+
+/// Sending one alert to one channel.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Delivery {
+    pub channel: Channel,
+    pub status: Status,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    next_try: DateTime<Utc>,
+}
+
+impl std::fmt::Display for Delivery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.status, &self.last_error) {
+            (Status::Sent(at), _) => write!(f, "Sent {}", at.format("%H:%M")),
+            (Status::Pending, None) => write!(f, "Sending"),
+            (Status::Pending, Some(err)) => {
+                write!(f, "Retrying ({}/{MAX_ATTEMPTS}): {err}", self.attempts)
+            }
+            (Status::Failed, err) => write!(f, "Failed: {}", err.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+/// An alert with every reference resolved, ready to be sent.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Message {
+    pub severity: Severity,
+    pub client: String,
+    pub task: String,
+    pub location: String,
+    pub message: String,
+    pub raised_by: String,
+    pub raised_at: DateTime<Utc>,
+}
+
+impl Message {
+    pub fn subject(&self) -> String {
+        format!("[{}] Alert for {}", self.severity, self.client)
+    }
+
+    pub fn text(&self) -> String {
+        format!(
+            "{}\n\nClient: {}\nTask: {}\nLocation: {}\nRaised by {} at {}\n",
+            self.message,
+            self.client,
+            self.task,
+            self.location,
+            self.raised_by,
+            self.raised_at.format("%Y-%m-%d %H:%M UTC"),
+        )
+    }
+}
+
+impl Store {
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    pub fn add_route(&mut self, route: Route) {
+        self.routes.push(route);
+    }
+
+    pub fn remove_route(&mut self, index: usize) {
+        if index < self.routes.len() {
+            self.routes.remove(index);
+        }
+    }
+
+    /// The channels alerts at `location` go to.
+    pub(super) fn channels_for(&self, location: Option<LocationId>) -> Vec<Channel> {
+        self.routes
+            .iter()
+            .filter(|r| r.location.is_none() || r.location == location)
+            .map(|r| r.channel.clone())
+            .collect()
+    }
+
+    pub(super) fn queue_deliveries(&self, location: Option<LocationId>) -> Vec<Delivery> {
+        let now = Utc::now();
+        self.channels_for(location)
+            .into_iter()
+            .map(|channel| Delivery {
+                channel,
+                status: Status::Pending,
+                attempts: 0,
+                last_error: None,
+                next_try: now,
+            })
+            .collect()
+    }
+
+    /// The deliveries that should be attempted now, by alert and index.
+    pub fn due_deliveries(&self, now: DateTime<Utc>) -> Vec<(AlertId, usize)> {
+        self.alerts
+            .iter()
+            .flat_map(|a| a.deliveries.iter().enumerate().map(move |(i, d)| (a, i, d)))
+            .filter(|(_, _, d)| d.status == Status::Pending && d.next_try <= now)
+            .map(|(a, i, _)| (a.id, i))
+            .collect()
+    }
+
+    /// When the next pending delivery is due, if there is one.
+    pub fn next_delivery(&self) -> Option<DateTime<Utc>> {
+        self.alerts
+            .iter()
+            .flat_map(|a| &a.deliveries)
+            .filter(|d| d.status == Status::Pending)
+            .map(|d| d.next_try)
+            .min()
+    }
+
+    /// What to send for a delivery, and where.
+    pub fn delivery_job(&self, alert: AlertId, index: usize) -> Option<(Channel, Message)> {
+        let a = self.alert(alert)?;
+        let delivery = a.deliveries.get(index)?;
+        let task = self.task(a.task)?;
+
+        let service = self.service(task.service).map_or("", |s| s.name.as_str());
+        let raised = a.raised()?;
+        let message = Message {
+            severity: a.severity,
+            client: self
+                .client(a.client)
+                .map(|c| c.name.clone())
+                .unwrap_or_default(),
+            task: format!("{service} {}", task.time_text()).trim().to_string(),
+            location: task
+                .location
+                .and_then(|l| self.location(l))
+                .map(|l| l.name.clone())
+                .unwrap_or_default(),
+            message: a.message.clone(),
+            raised_by: raised.by.clone(),
+            raised_at: raised.at,
+        };
+
+        Some((delivery.channel.clone(), message))
+    }
+
+    /// Record how an attempt went, scheduling a retry if it failed and there
+    /// are attempts left.
+    pub fn record_delivery(
+        &mut self,
+        alert: AlertId,
+        index: usize,
+        result: Result<(), String>,
+        now: DateTime<Utc>,
+    ) {
+        let Some(delivery) = self
+            .alerts
+            .iter_mut()
+            .find(|a| a.id == alert)
+            .and_then(|a| a.deliveries.get_mut(index))
+        else {
+            return;
+        };
+
+        delivery.attempts += 1;
+        match result {
+            Ok(()) => {
+                delivery.status = Status::Sent(now);
+                delivery.last_error = None;
+            }
+            Err(err) => {
+                tracing::warn!("Alert delivery to {} failed: {err}", delivery.channel);
+                delivery.last_error = Some(err);
+                if delivery.attempts >= MAX_ATTEMPTS {
+                    delivery.status = Status::Failed;
+                } else {
+                    let delay = RETRY_DELAY << (delivery.attempts - 1);
+                    delivery.next_try = now + chrono::Duration::seconds(delay);
+                }
+            }
+        }
+    }
+
+    /// Try a failed delivery again, with a fresh set of attempts.
+    pub fn retry_delivery(&mut self, alert: AlertId, index: usize) {
+        if let Some(delivery) = self
+            .alerts
+            .iter_mut()
+            .find(|a| a.id == alert)
+            .and_then(|a| a.deliveries.get_mut(index))
+        {
+            if delivery.status == Status::Failed {
+                delivery.status = Status::Pending;
+                delivery.attempts = 0;
+                delivery.next_try = Utc::now();
+            }
+        }
+    }
+}
+
+/// Sends mail through an SMTP server, without authentication or TLS.
+pub struct SmtpSink {
+    pub server: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Sink for SmtpSink {
+    fn send(&self, message: &Message) -> Result<(), String> {
+        let mut addresses = std::iter::once(&self.from).chain(&self.to);
+        if let Some(bad) = addresses.find(|a| !is_address(a)) {
+            return Err(format!("\"{bad}\" is not an email address"));
+        }
+        let stream = connect(&self.server)?;
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let mut writer = stream;
+
+        let mut command = |line: Option<String>, expect: u16| -> Result<(), String> {
+            if let Some(line) = &line {
+                write!(writer, "{line}\r\n").map_err(|e| e.to_string())?;
+            }
+            let code = smtp_reply(&mut reader)?;
+            if code == expect {
+                Ok(())
+            } else {
+                let sent = line.unwrap_or_else(|| "connecting".to_string());
+                Err(format!("mail server answered {code} to {sent}"))
+            }
+        };
+
+        command(None, 220)?;
+        command(Some("HELO zdoc".to_string()), 250)?;
+        command(Some(format!("MAIL FROM:<{}>", self.from)), 250)?;
+        for to in &self.to {
+            command(Some(format!("RCPT TO:<{to}>")), 250)?;
+        }
+        command(Some("DATA".to_string()), 354)?;
+
+        let mut data = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\n\r\n",
+            self.from,
+            self.to.join(", "),
+            header(&message.subject()),
+            message.raised_at.to_rfc2822(),
+        );
+        for line in message.text().lines() {
+            // A line starting with a dot needs another one, or it could end
+            // the message early
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(&line.replace('\r', ""));
+            data.push_str("\r\n");
+        }
+        data.push('.');
+        command(Some(data), 250)?;
+
+        command(Some("QUIT".to_string()), 221)
+    }
+}
+
+/// Whether `address` can go into a mail command or header as it is: it
+/// needs an `@`, and nothing that would end the line or the address early.
+fn is_address(address: &str) -> bool {
+    address.contains('@')
+        && !address
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '<' | '>' | ','))
+}
+
+/// A header value on one line, so client names or task text with line
+/// breaks in them can't add headers of their own.
+fn header(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// The code of the next reply, skipping the lines of multi-line replies.
+fn smtp_reply(reader: &mut impl BufRead) -> Result<u16, String> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            return Err("mail server closed the connection".to_string());
+        }
+        let code = line
+            .get(..3)
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(|| format!("mail server sent \"{}\"", line.trim()))?;
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(code);
+        }
+    }
+}
+
+/// POSTs the [`Message`] as JSON.
+pub struct WebhookSink {
+    pub url: String,
+}
+
+impl Sink for WebhookSink {
+    fn send(&self, message: &Message) -> Result<(), String> {
+        let (host, path) = split_url(&self.url)?;
+        let body = serde_json::to_string(message).map_err(|e| e.to_string())?;
+
+        let address = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{host}:80")
+        };
+        let request = format!(
+            "POST {path} HTTP/1.1\r\n\
+             Host: {host}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n\
+             {body}",
+            body.len()
+        );
+        let mut stream = connect(&address)?;
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        let mut status = String::new();
+        BufReader::new(stream)
+            .read_line(&mut status)
+            .map_err(|e| e.to_string())?;
+        let code: u16 = status
+            .split_whitespace()
+            .nth(1)
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(|| format!("webhook sent \"{}\"", status.trim()))?;
+
+        if (200..300).contains(&code) {
+            Ok(())
+        } else {
+            Err(format!("webhook answered {}", status.trim()))
+        }
+    }
+}
+
+/// The `host[:port]` and path of an `http://` URL.
+pub(super) fn split_url(url: &str) -> Result<(&str, &str), String> {
+    let rest = url
+        .trim()
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("\"{url}\" is not an http:// URL"))?;
+    let (host, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        return Err(format!("\"{url}\" has no host"));
+    }
+    // They go into the request line and headers as they are
+    if rest.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!(
+            "\"{}\" has spaces or line breaks in it",
+            url.trim()
+        ));
+    }
+    Ok((host, path))
+}
+
+pub(super) fn connect(address: &str) -> Result<TcpStream, String> {
+    let addr = address
+        .to_socket_addrs()
+        .map_err(|e| format!("{address}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("{address} did not resolve"))?;
+    let stream =
+        TcpStream::connect_timeout(&addr, TIMEOUT).map_err(|e| format!("{address}: {e}"))?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+/// Appends one line per alert.
+pub struct FileSink {
+    pub path: String,
+}
+
+impl Sink for FileSink {
+    fn send(&self, message: &Message) -> Result<(), String> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("{}: {e}", self.path))?;
+        writeln!(
+            file,
+            "{}\t{}\t{}\t{}",
+            message.raised_at.to_rfc3339(),
+            message.subject(),
+            message.task,
+            message.message.replace('\n', " ")
+        )
+        .map_err(|e| format!("{}: {e}", self.path))
+    }
+}
+
+pub struct LogSink;
+
+impl Sink for LogSink {
+    fn send(&self, message: &Message) -> Result<(), String> {
+        tracing::info!("{}: {}", message.subject(), message.message);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    fn message() -> Message {
+        Message {
+            severity: Severity::Urgent,
+            client: "Peter Groot".to_string(),
+            task: "Medication 08:30-08:45".to_string(),
+            location: "Location Y".to_string(),
+            message: "Refused medication\n.and spat it out".to_string(),
+            raised_by: "Ann".to_string(),
+            raised_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn smtp_sink_talks_to_a_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = listener.local_addr().unwrap().to_string();
+
+        // Just enough of an SMTP server to take one message
+        let stand_in = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut transcript = String::new();
+
+            writer.write_all(b"220-localhost\r\n220 ready\r\n").unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                transcript.push_str(&line);
+                let reply: &[u8] = if in_data {
+                    if line != ".\r\n" {
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else if line.starts_with("DATA") {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line.starts_with("QUIT") {
+                    writer.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).unwrap();
+            }
+            transcript
+        });
+
+        let sink = SmtpSink {
+            server,
+            from: "zdoc@example.com".to_string(),
+            to: vec!["nurse@example.com".to_string()],
+        };
+        sink.send(&message()).unwrap();
+
+        let transcript = stand_in.join().unwrap();
+        assert!(transcript.contains("RCPT TO:<nurse@example.com>\r\n"));
+        assert!(transcript.contains("Subject: [Urgent] Alert for Peter Groot\r\n"));
+        assert!(transcript.contains("\r\n..and spat it out\r\n"));
+    }
+
+    #[test]
+    fn line_breaks_never_reach_mail_headers() {
+        let mut message = message();
+        message.client = "Peter\r\nBcc: everyone@example.com".to_string();
+        assert!(!header(&message.subject()).contains(['\r', '\n']));
+
+        let channel = Channel::Email {
+            server: "localhost:25".to_string(),
+            from: "zdoc@example.com".to_string(),
+            to: vec!["nurse@example.com>\r\nRCPT TO:<other@example.com".to_string()],
+        };
+        assert!(channel.check().is_err());
+
+        // Refused before anything is sent, so no server is needed
+        let sink = SmtpSink {
+            server: "127.0.0.1:1".to_string(),
+            from: "zdoc@example.com\nBcc: x@example.com".to_string(),
+            to: vec!["nurse@example.com".to_string()],
+        };
+        assert!(sink
+            .send(&message)
+            .unwrap_err()
+            .contains("not an email address"));
+    }
+
+    #[test]
+    fn webhook_sink_posts_json_and_checks_the_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hooks/alerts", listener.local_addr().unwrap());
+
+        let stand_in = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in ["200 OK", "500 Internal Server Error"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(len) = line.strip_prefix("Content-Length: ") {
+                        length = len.trim().parse().unwrap();
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                request.push_str(&String::from_utf8(body).unwrap());
+                requests.push(request);
+                write!(stream, "HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").unwrap();
+            }
+            requests
+        });
+
+        let sink = WebhookSink { url };
+        sink.send(&message()).unwrap();
+        let err = sink.send(&message()).unwrap_err();
+        assert!(err.contains("500"));
+
+        let requests = stand_in.join().unwrap();
+        assert!(requests[0].starts_with("POST /hooks/alerts HTTP/1.1\r\n"));
+        assert!(requests[0].contains("\"severity\":\"Urgent\""));
+    }
+
+    #[test]
+    fn webhook_urls_with_line_breaks_are_refused_unsent() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        for url in [
+            format!("http://{address}/hooks\r\nX-Injected: yes"),
+            format!("http://{address}/hooks alerts"),
+            format!("http://{address}\n/hooks"),
+        ] {
+            let channel = Channel::Webhook { url: url.clone() };
+            assert!(channel.check().is_err(), "{url:?}");
+            assert!(WebhookSink { url }.send(&message()).is_err());
+        }
+
+        // Nothing got as far as connecting
+        listener.set_nonblocking(true).unwrap();
+        assert!(listener.accept().is_err());
+    }
+
+    #[test]
+    fn file_sink_appends_a_line_per_alert() {
+        let path = std::env::temp_dir().join(format!("zdoc-alerts-{}.log", std::process::id()));
+        let sink = FileSink {
+            path: path.to_string_lossy().into_owned(),
+        };
+
+        sink.send(&message()).unwrap();
+        sink.send(&message()).unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written.lines().count(), 2);
+        assert!(written.contains("Refused medication .and spat it out"));
+    }
+
+    #[test]
+    fn failed_deliveries_back_off_then_give_up() {
+        let mut store = Store::sample();
+        let task = store.tasks()[3].id;
+        let y = store.task(task).unwrap().location;
+        store.add_route(Route {
+            location: y,
+            channel: Channel::Log,
+        });
+        store.add_route(Route {
+            location: None,
+            channel: Channel::File {
+                path: "alerts.log".to_string(),
+            },
+        });
+        // Another location's channel isn't used
+        store.add_route(Route {
+            location: Some(store.locations()[0].id),
+            channel: Channel::Webhook {
+                url: "http://localhost/".to_string(),
+            },
+        });
+
+        let alert = store
+            .raise_alert(task, Severity::Urgent, "Refused medication", "Ann")
+            .unwrap();
+        let now = Utc::now();
+        assert_eq!(store.due_deliveries(now), vec![(alert, 0), (alert, 1)]);
+
+        let (channel, message) = store.delivery_job(alert, 0).unwrap();
+        assert_eq!(channel, Channel::Log);
+        assert_eq!(message.client, "Peter Groot");
+        assert_eq!(message.location, "Location Y");
+        store.record_delivery(alert, 0, Ok(()), now);
+
+        let mut at = now;
+        for attempt in 1..=MAX_ATTEMPTS {
+            store.record_delivery(alert, 1, Err("disk full".to_string()), at);
+            if attempt < MAX_ATTEMPTS {
+                assert!(store.due_deliveries(at).is_empty());
+                at = store.next_delivery().unwrap();
+                assert_eq!(store.due_deliveries(at), vec![(alert, 1)]);
+            }
+        }
+        assert_eq!(
+            at - now,
+            chrono::Duration::seconds(RETRY_DELAY * (1 + 2 + 4 + 8))
+        );
+
+        let deliveries = &store.alert(alert).unwrap().deliveries;
+        assert!(matches!(deliveries[0].status, Status::Sent(_)));
+        assert_eq!(deliveries[1].status, Status::Failed);
+        assert_eq!(store.next_delivery(), None);
+
+        store.retry_delivery(alert, 1);
+        assert_eq!(store.due_deliveries(Utc::now()), vec![(alert, 1)]);
+    }
+}
@@ -0,0 +1,202 @@
+//! Writing an assignment sheet out as CSV or a printable HTML schedule.
+
+use super::{Store, Task};
+
+/// One task, with every reference resolved to the text shown to people.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Line {
+    assignee: String,
+    client: String,
+    task: String,
+    location: String,
+    date: String,
+    time: String,
+}
+
+impl Line {
+    fn new(store: &Store, task: &Task) -> Self {
+        let service = store
+            .service(task.service)
+            .map(|s| s.name.clone())
+            .unwrap_or_default();
+
+        Self {
+            assignee: task
+                .assignee
+                .and_then(|a| store.caretaker(a))
+                .map(|a| a.name.clone())
+                .unwrap_or_default(),
+            client: store
+                .client(task.client)
+                .map(|c| c.name.clone())
+                .unwrap_or_default(),
+            task: if task.description.is_empty() {
+                service
+            } else {
+                format!("{service} - {}", task.description)
+            },
+            location: task
+                .location
+                .and_then(|l| store.location(l))
+                .map(|l| l.name.clone())
+                .unwrap_or_default(),
+            date: task.date.map(|d| d.to_string()).unwrap_or_default(),
+            time: task.time_text(),
+        }
+    }
+}
+
+/// The tasks as CSV, in the order given, with a header row.
+pub fn csv<'a>(store: &Store, tasks: impl IntoIterator<Item = &'a Task>) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let header = ["Assignee", "Client", "Task", "Location", "Date", "Time"];
+
+    // Writing to a Vec can't fail
+    writer.write_record(header).expect("write to memory");
+    for task in tasks {
+        let line = Line::new(store, task);
+        writer
+            .write_record([
+                &line.assignee,
+                &line.client,
+                &line.task,
+                &line.location,
+                &line.date,
+                &line.time,
+            ])
+            .expect("write to memory");
+    }
+
+    let bytes = writer.into_inner().expect("flush to memory");
+    String::from_utf8(bytes).expect("csv of strings is utf-8")
+}
+
+/// A self contained HTML page with one section per caretaker, each starting
+/// on a new page when printed. Unassigned tasks come last.
+pub fn html<'a>(store: &Store, title: &str, tasks: impl IntoIterator<Item = &'a Task>) -> String {
+    let mut lines: Vec<Line> = tasks.into_iter().map(|t| Line::new(store, t)).collect();
+    lines.sort_by(|a, b| {
+        (a.assignee.is_empty(), &a.assignee, &a.date, &a.time).cmp(&(
+            b.assignee.is_empty(),
+            &b.assignee,
+            &b.date,
+            &b.time,
+        ))
+    });
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape(title)));
+    out.push_str(STYLE);
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+
+    if lines.is_empty() {
+        out.push_str("<p>No tasks.</p>\n");
+    }
+
+    let mut rest = lines.as_slice();
+    while let Some(first) = rest.first() {
+        let len = rest
+            .iter()
+            .position(|l| l.assignee != first.assignee)
+            .unwrap_or(rest.len());
+        let (group, tail) = rest.split_at(len);
+        rest = tail;
+
+        let name = match first.assignee.as_str() {
+            "" => "Unassigned",
+            name => name,
+        };
+        out.push_str("<section>\n");
+        out.push_str(&format!("<h2>{}</h2>\n", escape(name)));
+        out.push_str(
+            "<table>\n<tr><th>Date</th><th>Time</th><th>Client</th><th>Task</th><th>Location</th></tr>\n",
+        );
+        for line in group {
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&line.date),
+                escape(&line.time),
+                escape(&line.client),
+                escape(&line.task),
+                escape(&line.location),
+            ));
+        }
+        out.push_str("</table>\n</section>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+const STYLE: &str = "<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+th, td { border: 1px solid #888; padding: 0.4em 0.6em; text-align: left; }
+th { background: #eee; }
+@media print {
+  body { margin: 0; }
+  section { break-after: page; }
+  section:last-child { break-after: auto; }
+}
+</style>
+";
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assigned_sample() -> Store {
+        let mut store = Store::sample();
+        let jim = store.caretakers()[0].id;
+        let first = store.tasks()[0].id;
+        let last = store.tasks()[6].id;
+        store.assign(first, jim).unwrap();
+        store.assign(last, jim).unwrap();
+        store
+    }
+
+    #[test]
+    fn csv_has_a_row_per_task() {
+        let store = assigned_sample();
+        let out = csv(&store, store.tasks());
+        let mut rows = out.lines();
+
+        assert_eq!(rows.next(), Some("Assignee,Client,Task,Location,Date,Time"));
+        let first = rows.next().unwrap();
+        assert!(first.starts_with("Jim Butz,Angela Johnson,Dog Therapy,Location X,"));
+        assert!(first.ends_with(",09:00-10:00"));
+        assert_eq!(rows.count(), 7);
+    }
+
+    #[test]
+    fn html_groups_by_caretaker_with_unassigned_last() {
+        let mut store = assigned_sample();
+        let client = store.find_or_add_client("Tom & <Jerry>");
+        let service = store.service_named("Dog Therapy").unwrap().id;
+        store.add_task(client, service).unwrap();
+        let out = html(&store, "Schedule", store.tasks());
+
+        let jim = out.find("<h2>Jim Butz</h2>").unwrap();
+        let unassigned = out.find("<h2>Unassigned</h2>").unwrap();
+        assert!(jim < unassigned);
+        assert_eq!(out.matches("<section>").count(), 2);
+        assert!(out.contains("<td>Tom &amp; &lt;Jerry&gt;</td>"));
+    }
+}
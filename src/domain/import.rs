@@ -0,0 +1,329 @@
+//! Loading caretaker rosters and task lists from CSV.
+//!
+//! Files need a header row; columns are matched by name, so their order doesn't
+//! matter. Rows that fail validation are skipped and reported, the rest are
+//! added to the [`Store`].
+
+use super::Store;
+
+/// A row that could not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// 1 based line in the file, the header is line 1
+    pub line: u64,
+    pub message: String,
+}
+
+/// The outcome of importing one file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub added: usize,
+    pub errors: Vec<RowError>,
+}
+
+impl Report {
+    fn error(&mut self, line: u64, message: impl Into<String>) {
+        self.errors.push(RowError {
+            line,
+            message: message.into(),
+        });
+    }
+}
+
+/// The header names accepted for each column.
+const NAME: &[&str] = &["name", "caretaker"];
+const CREDENTIAL: &[&str] = &["credential", "credentials", "cred"];
+const CLIENT: &[&str] = &["client", "client name"];
+const TASK: &[&str] = &["task", "description"];
+const LOCATION: &[&str] = &["location", "site"];
+const SERVICE: &[&str] = &["service"];
+const DATE: &[&str] = &["date"];
+
+/// Import a roster with `name` and `credential` columns.
+pub fn caretakers(store: &mut Store, data: &[u8]) -> Report {
+    let mut report = Report::default();
+    let Some(mut rows) = Rows::new(data, &[NAME, CREDENTIAL], &mut report) else {
+        return report;
+    };
+
+    while let Some(row) = rows.next(&mut report) {
+        let name = row.get(NAME);
+        let cred = row.get(CREDENTIAL);
+
+        if name.is_empty() {
+            report.error(row.line, "name is empty");
+        } else if store.caretaker_named(name).is_some() {
+            report.error(row.line, format!("{name} is already on the roster"));
+        } else {
+            store.add_caretaker(name, cred);
+            report.added += 1;
+        }
+    }
+
+    report
+}
+
+/// Import a task list with `client`, `task`, `location`, `service` and `date`
+/// columns. Clients and locations that don't exist yet are added, services must
+/// already be in the catalog.
+pub fn tasks(store: &mut Store, data: &[u8]) -> Report {
+    let mut report = Report::default();
+    let Some(mut rows) = Rows::new(data, &[CLIENT, TASK, LOCATION, SERVICE, DATE], &mut report)
+    else {
+        return report;
+    };
+
+    while let Some(row) = rows.next(&mut report) {
+        let client = row.get(CLIENT);
+        let service = row.get(SERVICE);
+        let location = row.get(LOCATION);
+
+        let mut problems = Vec::new();
+        if client.is_empty() {
+            problems.push("client is empty".to_string());
+        }
+        let service = if service.is_empty() {
+            problems.push("service is empty".to_string());
+            None
+        } else if let Some(s) = store.service_named(service) {
+            Some(s.id)
+        } else {
+            problems.push(unknown_service(store, service));
+            None
+        };
+        let date = match parse_date(row.get(DATE)) {
+            Ok(date) => Some(date),
+            Err(msg) => {
+                problems.push(msg);
+                None
+            }
+        };
+
+        let Some(service) = service.filter(|_| problems.is_empty()) else {
+            report.error(row.line, problems.join(", "));
+            continue;
+        };
+
+        let client = store.find_or_add_client(client);
+        let location = (!location.is_empty()).then(|| store.find_or_add_location(location));
+        let id = store
+            .add_task(client, service)
+            .expect("client was just added and service is in the catalog");
+        let task = store.task_mut(id).expect("task was just added");
+        task.description = row.get(TASK).to_string();
+        task.location = location;
+        task.date = date;
+
+        report.added += 1;
+    }
+
+    report
+}
+
+/// Names the closest catalog entry, so typos like "Stress Therepy" are easy to fix.
+fn unknown_service(store: &Store, name: &str) -> String {
+    let closest = store
+        .services()
+        .iter()
+        .map(|s| (edit_distance(&s.name, name), &s.name))
+        .min();
+
+    match closest {
+        Some((distance, known)) if distance <= 3 => {
+            format!("\"{name}\" is not in the service catalog, did you mean \"{known}\"?")
+        }
+        _ => format!("\"{name}\" is not in the service catalog"),
+    }
+}
+
+/// Levenshtein distance, ignoring case.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != cb);
+            row.push(substitute.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+
+    prev[b.len()]
+}
+
+/// Accepts ISO dates and the month/day/year dates spreadsheets like to write.
+fn parse_date(text: &str) -> Result<chrono::NaiveDate, String> {
+    if text.is_empty() {
+        return Err("date is empty".to_string());
+    }
+
+    ["%Y-%m-%d", "%m/%d/%Y"]
+        .iter()
+        .find_map(|fmt| chrono::NaiveDate::parse_from_str(text, fmt).ok())
+        .ok_or_else(|| format!("\"{text}\" is not a date, expected YYYY-MM-DD or MM/DD/YYYY"))
+}
+
+/// Reads records and maps the wanted columns to their position in the file.
+struct Rows<'a> {
+    reader: csv::Reader<&'a [u8]>,
+    columns: Vec<(&'static [&'static str], usize)>,
+}
+
+struct Row<'a> {
+    line: u64,
+    record: csv::StringRecord,
+    columns: &'a [(&'static [&'static str], usize)],
+}
+
+impl<'a> Rows<'a> {
+    fn new(
+        data: &'a [u8],
+        wanted: &[&'static [&'static str]],
+        report: &mut Report,
+    ) -> Option<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(data);
+
+        let headers = match reader.headers() {
+            Ok(headers) => headers.clone(),
+            Err(err) => {
+                report.error(1, format!("could not read the header row: {err}"));
+                return None;
+            }
+        };
+
+        let mut columns = Vec::new();
+        let mut missing = Vec::new();
+        for names in wanted {
+            match headers
+                .iter()
+                .position(|h| names.iter().any(|n| h.eq_ignore_ascii_case(n)))
+            {
+                Some(pos) => columns.push((*names, pos)),
+                None => missing.push(names[0]),
+            }
+        }
+
+        if !missing.is_empty() {
+            report.error(1, format!("missing column(s): {}", missing.join(", ")));
+            return None;
+        }
+
+        Some(Self { reader, columns })
+    }
+
+    /// The next readable row, recording unreadable ones in `report`.
+    fn next(&mut self, report: &mut Report) -> Option<Row<'_>> {
+        loop {
+            let mut record = csv::StringRecord::new();
+            match self.reader.read_record(&mut record) {
+                Ok(false) => return None,
+                Ok(true) => {
+                    // Skip rows a spreadsheet left blank
+                    if record.iter().all(str::is_empty) {
+                        continue;
+                    }
+                    let line = record.position().map_or(0, |p| p.line());
+                    return Some(Row {
+                        line,
+                        record,
+                        columns: &self.columns,
+                    });
+                }
+                Err(err) => {
+                    let line = err.position().map_or(0, |p| p.line());
+                    report.error(line, err.to_string());
+                }
+            }
+        }
+    }
+}
+
+impl Row<'_> {
+    /// The trimmed value of a column, empty if the row is too short.
+    fn get(&self, column: &[&str]) -> &str {
+        self.columns
+            .iter()
+            .find(|(names, _)| *names == column)
+            .and_then(|(_, pos)| self.record.get(*pos))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_roster_and_reports_bad_rows() {
+        let mut store = Store::default();
+        let csv = "Name,Credential\n\
+                   Jim Butz,Nurse RN\n\
+                   ,DSP\n\
+                   jim butz,None\n\
+                   Dave Butz,\n";
+
+        let report = caretakers(&mut store, csv.as_bytes());
+
+        assert_eq!(report.added, 2);
+        assert_eq!(
+            report.errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert!(store
+            .caretaker_named("Dave Butz")
+            .unwrap()
+            .credentials
+            .is_empty());
+    }
+
+    #[test]
+    fn missing_columns_import_nothing() {
+        let mut store = Store::default();
+        let report = tasks(&mut store, b"client,service\nDax Quil,Verbal Therapy\n");
+
+        assert_eq!(report.added, 0);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].message.contains("task"));
+        assert!(store.tasks().is_empty());
+    }
+
+    #[test]
+    fn imports_tasks_in_any_column_order() {
+        let mut store = Store::sample();
+        let before = store.tasks().len();
+        let csv = "Date,Service,Client,Location,Task\n\
+                   2023-03-01,Dog Therapy,Dax Quil,Location X,Morning walk\n\
+                   03/02/2023,handwashing,New Client,,\n\
+                   tomorrow,Dog Therapy,Dax Quil,Location X,\n\
+                   2023-03-01,,Dax Quil,Location X,\n\
+                   2023-03-01,Stress Therepy,Gamora Thanos,Location Z,\n";
+
+        let report = tasks(&mut store, csv.as_bytes());
+
+        assert_eq!(report.added, 2);
+        assert_eq!(
+            report.errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![4, 5, 6]
+        );
+        assert!(report.errors[2]
+            .message
+            .contains("did you mean \"Stress Therapy\""));
+
+        let added = store.tasks()[before..].to_vec();
+        assert_eq!(added[0].description, "Morning walk");
+        assert!(added[0].location.is_some());
+        assert_eq!(added[1].date, chrono::NaiveDate::from_ymd_opt(2023, 3, 2));
+        assert!(added[1].location.is_none());
+        assert_eq!(
+            store.service_named("Handwashing").unwrap().id,
+            added[1].service
+        );
+        assert_eq!(store.find_or_add_client("new client"), added[1].client);
+    }
+}
@@ -0,0 +1,109 @@
+//! Tasks made from clients' care plans.
+
+use chrono::NaiveDate;
+
+use super::{PlanId, PlannedService, ServiceId, Store, Task, TaskId};
+
+/// How many days ahead tasks are made from the care plans.
+pub const HORIZON_DAYS: i64 = 14;
+
+/// Whether tasks for `date` should be made from the care plans on `today`:
+/// from today up to [`HORIZON_DAYS`] ahead. Past days are left as they were.
+pub fn in_horizon(today: NaiveDate, date: NaiveDate) -> bool {
+    date >= today && (date - today).num_days() <= HORIZON_DAYS
+}
+
+impl Store {
+    /// A new care plan entry for `service`, given every day. Add it to a
+    /// client's [`care_plan`](super::Client::care_plan).
+    pub fn plan_service(&mut self, service: ServiceId) -> PlannedService {
+        PlannedService::new(PlanId(self.next_id()), service)
+    }
+
+    /// Add the tasks the care plans call for on `date` that haven't been made
+    /// yet, returning how many were added.
+    ///
+    /// Each occurrence is only made once, so whatever is done to it afterwards,
+    /// changing its times or assigning it, is kept. Changing a plan only affects
+    /// the days that haven't been generated yet.
+    pub fn generate_tasks(&mut self, date: NaiveDate) -> usize {
+        let due: Vec<Task> = self
+            .clients
+            .iter()
+            .flat_map(|c| c.care_plan.iter().map(move |p| (c, p)))
+            .filter(|(_, p)| p.occurs_on(date))
+            .filter(|(_, p)| {
+                !self
+                    .tasks
+                    .iter()
+                    .any(|t| t.plan == Some(p.id) && t.date == Some(date))
+            })
+            .map(|(client, planned)| {
+                let mut task = Task::new(TaskId(0), client.id, planned.service);
+                task.location = client.location;
+                task.date = Some(date);
+                task.start = planned.start;
+                task.end = planned.end;
+                task.plan = Some(planned.id);
+                task
+            })
+            .collect();
+
+        let added = due.len();
+        for mut task in due {
+            task.id = TaskId(self.next_id());
+            self.tasks.push(task);
+        }
+        added
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occurrences_are_made_once_and_keep_their_changes() {
+        let mut store = Store::default();
+        let peter = store.add_client("Peter Groot");
+        let hands = store.add_service("Handwashing");
+        let dog = store.add_service("Dog Therapy");
+        let jim = store.add_caretaker("Jim Butz", "DSP");
+
+        let daily = store.plan_service(hands);
+        let mut weekly = store.plan_service(dog);
+        // Mondays only
+        weekly.days = [true, false, false, false, false, false, false];
+        weekly.start = chrono::NaiveTime::from_hms_opt(9, 0, 0);
+        store
+            .client_mut(peter)
+            .unwrap()
+            .care_plan
+            .extend([daily, weekly]);
+
+        // 2023-03-06 is a Monday
+        let monday = NaiveDate::from_ymd_opt(2023, 3, 6).unwrap();
+        assert_eq!(store.generate_tasks(monday), 2);
+        assert_eq!(store.generate_tasks(monday.succ_opt().unwrap()), 1);
+
+        let dog_task = store.tasks().iter().find(|t| t.service == dog).unwrap().id;
+        store.assign(dog_task, jim).unwrap();
+        store.task_mut(dog_task).unwrap().start = chrono::NaiveTime::from_hms_opt(10, 0, 0);
+
+        assert_eq!(store.generate_tasks(monday), 0);
+        let task = store.task(dog_task).unwrap();
+        assert_eq!(task.assignee, Some(jim));
+        assert_eq!(task.start, chrono::NaiveTime::from_hms_opt(10, 0, 0));
+        assert_eq!(store.tasks().len(), 3);
+    }
+
+    #[test]
+    fn only_the_coming_days_are_planned() {
+        let today = NaiveDate::from_ymd_opt(2023, 3, 6).unwrap();
+        let days = |n| today + chrono::Duration::days(n);
+        assert!(in_horizon(today, today));
+        assert!(in_horizon(today, days(HORIZON_DAYS)));
+        assert!(!in_horizon(today, days(HORIZON_DAYS + 1)));
+        assert!(!in_horizon(today, days(-1)));
+    }
+}
@@ -0,0 +1,50 @@
+//! Keeping the [`Store`] between runs.
+//!
+//! The app talks to a [`Backend`] rather than a particular database, so each
+//! platform can keep the data wherever suits it.
+
+#[cfg(target_arch = "wasm32")]
+pub mod indexed_db;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sqlite;
+
+use std::task::Poll;
+
+use super::Store;
+
+/// Somewhere the [`Store`] is kept.
+pub trait Backend {
+    /// The saved store, or `None` if nothing has been saved yet. Backends that
+    /// load in the background are `Pending` until it has been read.
+    fn load(&mut self) -> Poll<Result<Option<Store>, Error>>;
+
+    /// Replace whatever was saved with `store`. Either all of it is saved or
+    /// none of it is. Backends that save in the background report a failure
+    /// on the next save.
+    fn save(&mut self, store: &Store) -> Result<(), Error>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The data was saved by a newer version of zDoc, so it is left untouched
+    TooNew { version: u32, supported: u32 },
+    /// There is no room left, e.g. the browser's storage quota is used up
+    Quota,
+    /// The database itself failed, e.g. the file can't be opened
+    Backend(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::TooNew { version, supported } => write!(
+                f,
+                "the data is from a newer zDoc (version {version}, this one reads up to {supported})"
+            ),
+            Error::Quota => write!(f, "storage is full, free up space to keep changes"),
+            Error::Backend(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
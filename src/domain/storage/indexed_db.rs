@@ -0,0 +1,192 @@
+//! The web [`Backend`]: the store is kept as JSON in the browser's IndexedDB,
+//! so the page keeps working offline and across reloads.
+//!
+//! IndexedDB only answers through callbacks, so loading finishes on a later
+//! frame and failed saves are reported by the next save.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::task::Poll;
+
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{DomException, IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+use super::{Backend, Error};
+use crate::domain::Store;
+
+const NAME: &str = "zdoc";
+/// Bump when the object stores change, and make the change in `upgrade`.
+const VERSION: u32 = 1;
+const DATA: &str = "data";
+const KEY: &str = "store";
+
+/// What the callbacks hand back.
+#[derive(Default)]
+struct Shared {
+    db: Option<IdbDatabase>,
+    loaded: Option<Result<Option<Store>, Error>>,
+    /// Why the last save failed, reported by the next one
+    failed: Option<Error>,
+}
+
+pub struct IndexedDb {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl IndexedDb {
+    /// Start opening the database, upgrading it if needed, and reading the
+    /// saved store.
+    pub fn open() -> Result<Self, Error> {
+        let factory = web_sys::window()
+            .ok_or_else(|| Error::Backend("not running in a browser window".to_string()))?
+            .indexed_db()
+            .map_err(js_error)?
+            .ok_or_else(|| Error::Backend("this browser has no IndexedDB".to_string()))?;
+        let request = factory.open_with_u32(NAME, VERSION).map_err(js_error)?;
+        let shared = Rc::new(RefCell::new(Shared::default()));
+
+        let on_upgrade = {
+            let request = request.clone();
+            Closure::once_into_js(move |event: web_sys::IdbVersionChangeEvent| {
+                if let Err(err) = upgrade(&request, event.old_version()) {
+                    tracing::error!("Couldn't upgrade IndexedDB: {err:?}");
+                }
+            })
+        };
+        request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+        let on_success = {
+            let (request, shared) = (request.clone(), shared.clone());
+            Closure::once_into_js(move |_: web_sys::Event| {
+                let opened = request
+                    .result()
+                    .and_then(|db| db.dyn_into::<IdbDatabase>())
+                    .and_then(|db| {
+                        read(&shared, &db)?;
+                        Ok(db)
+                    });
+                let mut shared = shared.borrow_mut();
+                match opened {
+                    Ok(db) => shared.db = Some(db),
+                    Err(err) => shared.loaded = Some(Err(js_error(err))),
+                }
+            })
+        };
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+
+        let on_error = {
+            let (request, shared) = (request.clone(), shared.clone());
+            Closure::once_into_js(move |_: web_sys::Event| {
+                shared.borrow_mut().loaded = Some(Err(request_error(&request)));
+            })
+        };
+        request.set_onerror(Some(on_error.unchecked_ref()));
+
+        Ok(Self { shared })
+    }
+}
+
+impl Backend for IndexedDb {
+    fn load(&mut self) -> Poll<Result<Option<Store>, Error>> {
+        match self.shared.borrow_mut().loaded.take() {
+            Some(loaded) => Poll::Ready(loaded),
+            None => Poll::Pending,
+        }
+    }
+
+    fn save(&mut self, store: &Store) -> Result<(), Error> {
+        let previous = self.shared.borrow_mut().failed.take();
+
+        let json = serde_json::to_string(store).map_err(|err| Error::Backend(err.to_string()))?;
+        let shared = self.shared.borrow();
+        let db = shared
+            .db
+            .as_ref()
+            .ok_or_else(|| Error::Backend("the database isn't open yet".to_string()))?;
+        let tx = db
+            .transaction_with_str_and_mode(DATA, IdbTransactionMode::Readwrite)
+            .map_err(js_error)?;
+        tx.object_store(DATA)
+            .and_then(|data| data.put_with_key(&JsValue::from_str(&json), &JsValue::from_str(KEY)))
+            .map_err(js_error)?;
+
+        // Running out of quota aborts the transaction rather than failing the put
+        let on_abort = {
+            let (tx, shared) = (tx.clone(), self.shared.clone());
+            Closure::once_into_js(move |_: web_sys::Event| {
+                shared.borrow_mut().failed = Some(dom_error(tx.error()));
+            })
+        };
+        tx.set_onabort(Some(on_abort.unchecked_ref()));
+
+        previous.map_or(Ok(()), Err)
+    }
+}
+
+/// Bring the object stores up from `old_version`.
+fn upgrade(request: &IdbOpenDbRequest, old_version: f64) -> Result<(), JsValue> {
+    let db: IdbDatabase = request.result()?.dyn_into()?;
+    if old_version < 1.0 {
+        db.create_object_store(DATA)?;
+    }
+    Ok(())
+}
+
+/// Start reading the saved store into `shared.loaded`.
+fn read(shared: &Rc<RefCell<Shared>>, db: &IdbDatabase) -> Result<(), JsValue> {
+    let request = db
+        .transaction_with_str(DATA)?
+        .object_store(DATA)?
+        .get(&JsValue::from_str(KEY))?;
+
+    let on_success = {
+        let (request, shared) = (request.clone(), shared.clone());
+        Closure::once_into_js(move |_: web_sys::Event| {
+            let loaded = match request.result() {
+                Ok(value) if value.is_undefined() => Ok(None),
+                Ok(value) => value
+                    .as_string()
+                    .ok_or_else(|| Error::Backend("the saved data is corrupt".to_string()))
+                    .and_then(|json| {
+                        serde_json::from_str(&json).map_err(|err| Error::Backend(err.to_string()))
+                    })
+                    .map(Some),
+                Err(err) => Err(js_error(err)),
+            };
+            shared.borrow_mut().loaded = Some(loaded);
+        })
+    };
+    request.set_onsuccess(Some(on_success.unchecked_ref()));
+
+    let on_error = {
+        let (request, shared) = (request.clone(), shared.clone());
+        Closure::once_into_js(move |_: web_sys::Event| {
+            shared.borrow_mut().loaded = Some(Err(request_error(&request)));
+        })
+    };
+    request.set_onerror(Some(on_error.unchecked_ref()));
+
+    Ok(())
+}
+
+fn request_error(request: &IdbRequest) -> Error {
+    match request.error() {
+        Ok(err) => dom_error(err),
+        Err(err) => js_error(err),
+    }
+}
+
+fn dom_error(err: Option<DomException>) -> Error {
+    match err {
+        Some(err) if err.name() == "QuotaExceededError" => Error::Quota,
+        Some(err) => Error::Backend(err.message()),
+        None => Error::Backend("IndexedDB failed without saying why".to_string()),
+    }
+}
+
+fn js_error(err: JsValue) -> Error {
+    match err.dyn_into::<DomException>() {
+        Ok(err) => dom_error(Some(err)),
+        Err(err) => Error::Backend(format!("{err:?}")),
+    }
+}
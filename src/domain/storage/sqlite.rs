@@ -0,0 +1,473 @@
+//! The native [`Backend`]: one SQLite file holding everything, so it can be
+//! backed up by copying it.
+
+use std::path::Path;
+use std::task::Poll;
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use super::{Backend, Error};
+use crate::domain::{
+    Caretaker, CaretakerId, Client, ClientId, Credential, Location, LocationId, Modified, PlanId,
+    Service, ServiceId, Store, Task, TaskId,
+};
+
+/// The schema, one step per version. Only ever add to the end, the database
+/// records how many of these it has had applied.
+const MIGRATIONS: &[&str] = &[
+    // 1
+    "CREATE TABLE meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE locations (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL
+    );
+    CREATE TABLE services (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        required TEXT NOT NULL
+    );
+    CREATE TABLE clients (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        dob TEXT,
+        location INTEGER REFERENCES locations (id),
+        notes TEXT NOT NULL,
+        contacts TEXT NOT NULL,
+        care_plan TEXT NOT NULL
+    );
+    CREATE TABLE caretakers (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        location INTEGER REFERENCES locations (id),
+        active INTEGER NOT NULL,
+        availability TEXT NOT NULL
+    );
+    CREATE TABLE credentials (
+        caretaker INTEGER NOT NULL REFERENCES caretakers (id),
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        issued TEXT,
+        expires TEXT,
+        PRIMARY KEY (caretaker, position)
+    );
+    CREATE TABLE tasks (
+        id INTEGER PRIMARY KEY,
+        client INTEGER NOT NULL REFERENCES clients (id),
+        service INTEGER NOT NULL REFERENCES services (id),
+        description TEXT NOT NULL,
+        location INTEGER REFERENCES locations (id),
+        date TEXT,
+        start_time TEXT,
+        end_time TEXT,
+        plan INTEGER
+    );
+    CREATE TABLE assignments (
+        task INTEGER PRIMARY KEY REFERENCES tasks (id),
+        caretaker INTEGER NOT NULL REFERENCES caretakers (id)
+    );",
+    // 2
+    "ALTER TABLE tasks ADD COLUMN modified_by TEXT;
+    ALTER TABLE tasks ADD COLUMN modified_at TEXT;",
+    // 3
+    "ALTER TABLE tasks ADD COLUMN version INTEGER NOT NULL DEFAULT 0;",
+];
+
+/// Tables in the order they can be filled without breaking references.
+const TABLES: &[&str] = &[
+    "meta",
+    "locations",
+    "services",
+    "clients",
+    "caretakers",
+    "credentials",
+    "tasks",
+    "assignments",
+];
+
+pub struct Sqlite {
+    conn: Connection,
+}
+
+impl Sqlite {
+    /// Open the database at `path`, creating it if needed, and bring its
+    /// schema up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut conn = Connection::open(path)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut conn)?;
+        Ok(Self { conn })
+    }
+
+    fn read(&self) -> Result<Option<Store>, Error> {
+        let Some(next_id) = meta(&self.conn, "next_id")? else {
+            return Ok(None);
+        };
+
+        let mut store = Store {
+            next_id: next_id.parse().map_err(|_| bad("next_id"))?,
+            alerts: json(&meta(&self.conn, "alerts")?.unwrap_or_default(), "alerts")?,
+            notifications: json(
+                &meta(&self.conn, "notifications")?.unwrap_or_default(),
+                "notifications",
+            )?,
+            routes: json(&meta(&self.conn, "routes")?.unwrap_or_default(), "routes")?,
+            ..Store::default()
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name FROM locations ORDER BY rowid")?;
+        store.locations = stmt
+            .query_map([], |row| {
+                Ok(Location {
+                    id: LocationId(row.get(0)?),
+                    name: row.get(1)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, required FROM services ORDER BY rowid")?;
+        store.services = stmt
+            .query_map([], |row| {
+                Ok((
+                    ServiceId(row.get(0)?),
+                    row.get(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .map(|row| {
+                let (id, name, required) = row?;
+                Ok(Service {
+                    id,
+                    name,
+                    required: json(&required, "services.required")?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, dob, location, notes, contacts, care_plan
+             FROM clients ORDER BY rowid",
+        )?;
+        store.clients = stmt
+            .query_map([], |row| {
+                Ok((
+                    Client {
+                        id: ClientId(row.get(0)?),
+                        name: row.get(1)?,
+                        dob: row.get(2)?,
+                        location: row.get::<_, Option<u32>>(3)?.map(LocationId),
+                        notes: row.get(4)?,
+                        contacts: Vec::new(),
+                        care_plan: Vec::new(),
+                    },
+                    row.get::<_, String>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            })?
+            .map(|row| {
+                let (mut client, contacts, care_plan) = row?;
+                client.contacts = json(&contacts, "clients.contacts")?;
+                client.care_plan = json(&care_plan, "clients.care_plan")?;
+                Ok(client)
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, location, active, availability FROM caretakers ORDER BY rowid",
+        )?;
+        let mut credentials = self.conn.prepare(
+            "SELECT name, issued, expires FROM credentials
+             WHERE caretaker = ?1 ORDER BY position",
+        )?;
+        store.caretakers = stmt
+            .query_map([], |row| {
+                Ok((
+                    Caretaker {
+                        id: CaretakerId(row.get(0)?),
+                        name: row.get(1)?,
+                        credentials: Vec::new(),
+                        location: row.get::<_, Option<u32>>(2)?.map(LocationId),
+                        availability: Default::default(),
+                        active: row.get(3)?,
+                    },
+                    row.get::<_, String>(4)?,
+                ))
+            })?
+            .map(|row| {
+                let (mut caretaker, availability) = row?;
+                caretaker.availability = json(&availability, "caretakers.availability")?;
+                caretaker.credentials = credentials
+                    .query_map([caretaker.id.0], |row| {
+                        Ok(Credential {
+                            name: row.get(0)?,
+                            issued: row.get(1)?,
+                            expires: row.get(2)?,
+                        })
+                    })?
+                    .collect::<Result<_, _>>()?;
+                Ok(caretaker)
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT t.id, t.client, t.service, t.description, t.location, t.date,
+                    t.start_time, t.end_time, a.caretaker, t.plan, t.modified_by, t.modified_at,
+                    t.version
+             FROM tasks t LEFT JOIN assignments a ON a.task = t.id
+             ORDER BY t.rowid",
+        )?;
+        store.tasks = stmt
+            .query_map([], |row| {
+                Ok(Task {
+                    id: TaskId(row.get(0)?),
+                    client: ClientId(row.get(1)?),
+                    service: ServiceId(row.get(2)?),
+                    description: row.get(3)?,
+                    location: row.get::<_, Option<u32>>(4)?.map(LocationId),
+                    date: row.get(5)?,
+                    start: row.get(6)?,
+                    end: row.get(7)?,
+                    assignee: row.get::<_, Option<u32>>(8)?.map(CaretakerId),
+                    plan: row.get::<_, Option<u32>>(9)?.map(PlanId),
+                    modified: match (row.get(10)?, row.get(11)?) {
+                        (Some(by), Some(at)) => Some(Modified { by, at }),
+                        _ => None,
+                    },
+                    version: row.get(12)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(store))
+    }
+}
+
+impl Backend for Sqlite {
+    fn load(&mut self) -> Poll<Result<Option<Store>, Error>> {
+        Poll::Ready(self.read())
+    }
+
+    fn save(&mut self, store: &Store) -> Result<(), Error> {
+        let tx = self.conn.transaction()?;
+        for table in TABLES.iter().rev() {
+            tx.execute(&format!("DELETE FROM {table}"), [])?;
+        }
+
+        set_meta(&tx, "next_id", &store.next_id.to_string())?;
+        set_meta(&tx, "alerts", &to_json(&store.alerts)?)?;
+        set_meta(&tx, "notifications", &to_json(&store.notifications)?)?;
+        set_meta(&tx, "routes", &to_json(&store.routes)?)?;
+
+        for l in &store.locations {
+            tx.execute(
+                "INSERT INTO locations (id, name) VALUES (?1, ?2)",
+                params![l.id.0, l.name],
+            )?;
+        }
+        for s in &store.services {
+            tx.execute(
+                "INSERT INTO services (id, name, required) VALUES (?1, ?2, ?3)",
+                params![s.id.0, s.name, to_json(&s.required)?],
+            )?;
+        }
+        for c in &store.clients {
+            tx.execute(
+                "INSERT INTO clients (id, name, dob, location, notes, contacts, care_plan)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    c.id.0,
+                    c.name,
+                    c.dob,
+                    c.location.map(|l| l.0),
+                    c.notes,
+                    to_json(&c.contacts)?,
+                    to_json(&c.care_plan)?,
+                ],
+            )?;
+        }
+        for c in &store.caretakers {
+            tx.execute(
+                "INSERT INTO caretakers (id, name, location, active, availability)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    c.id.0,
+                    c.name,
+                    c.location.map(|l| l.0),
+                    c.active,
+                    to_json(&c.availability)?,
+                ],
+            )?;
+            for (position, cred) in c.credentials.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO credentials (caretaker, position, name, issued, expires)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![c.id.0, position, cred.name, cred.issued, cred.expires],
+                )?;
+            }
+        }
+        for t in &store.tasks {
+            tx.execute(
+                "INSERT INTO tasks
+                 (id, client, service, description, location, date, start_time, end_time, plan,
+                  modified_by, modified_at, version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    t.id.0,
+                    t.client.0,
+                    t.service.0,
+                    t.description,
+                    t.location.map(|l| l.0),
+                    t.date,
+                    t.start,
+                    t.end,
+                    t.plan.map(|p| p.0),
+                    t.modified.as_ref().map(|m| &m.by),
+                    t.modified.as_ref().map(|m| m.at),
+                    t.version,
+                ],
+            )?;
+            if let Some(caretaker) = t.assignee {
+                tx.execute(
+                    "INSERT INTO assignments (task, caretaker) VALUES (?1, ?2)",
+                    params![t.id.0, caretaker.0],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Apply the migrations the database hasn't had yet, all in one transaction.
+fn migrate(conn: &mut Connection) -> Result<(), Error> {
+    let supported = MIGRATIONS.len() as u32;
+    let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > supported {
+        return Err(Error::TooNew { version, supported });
+    }
+
+    let tx = conn.transaction()?;
+    for (done, sql) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", done as u32 + 1)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn meta(conn: &Connection, key: &str) -> Result<Option<String>, Error> {
+    Ok(conn
+        .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()?)
+}
+
+fn set_meta(tx: &Transaction<'_>, key: &str, value: &str) -> Result<(), Error> {
+    tx.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+/// Parts of the store without a table of their own are kept as JSON.
+fn to_json(value: &impl serde::Serialize) -> Result<String, Error> {
+    serde_json::to_string(value).map_err(|err| Error::Backend(err.to_string()))
+}
+
+fn json<T: serde::de::DeserializeOwned + Default>(text: &str, what: &str) -> Result<T, Error> {
+    if text.is_empty() {
+        return Ok(T::default());
+    }
+    serde_json::from_str(text).map_err(|_| bad(what))
+}
+
+fn bad(what: &str) -> Error {
+    Error::Backend(format!("{what} is corrupt"))
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        match err {
+            rusqlite::Error::SqliteFailure(e, _) if e.code == rusqlite::ErrorCode::DiskFull => {
+                Error::Quota
+            }
+            err => Error::Backend(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("zdoc-{}-{name}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn store_survives_reopening_the_file() {
+        let path = temp_path("reopen");
+        let mut store = Store::sample();
+        let jim = store.caretaker_named("Jim Butz").unwrap().id;
+        let task = store.tasks()[0].id;
+        store.set_user("Ann");
+        store.assign(task, jim).unwrap();
+        store.caretaker_mut(jim).unwrap().credentials[0].expires =
+            chrono::NaiveDate::from_ymd_opt(2030, 1, 31);
+
+        let mut db = Sqlite::open(&path).unwrap();
+        assert!(db.read().unwrap().is_none());
+        db.save(&store).unwrap();
+        drop(db);
+
+        let restored = Sqlite::open(&path).unwrap().read().unwrap().unwrap();
+        assert_eq!(restored.task(task).unwrap().assignee, Some(jim));
+        assert_eq!(
+            restored.task(task).unwrap().modified,
+            store.task(task).unwrap().modified
+        );
+        assert_eq!(restored.task(task).unwrap().version, 1);
+        assert_eq!(restored.tasks().len(), store.tasks().len());
+        assert_eq!(
+            restored.caretaker(jim).unwrap().credentials,
+            store.caretaker(jim).unwrap().credentials
+        );
+        assert_eq!(
+            restored.clients()[0].care_plan,
+            store.clients()[0].care_plan
+        );
+        assert_eq!(restored.next_id, store.next_id);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn newer_databases_are_left_alone() {
+        let path = temp_path("newer");
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", 99)
+            .unwrap();
+
+        assert_eq!(
+            Sqlite::open(&path).err(),
+            Some(Error::TooNew {
+                version: 99,
+                supported: MIGRATIONS.len() as u32
+            })
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
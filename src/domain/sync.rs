@@ -0,0 +1,900 @@
+//! Sharing one schedule between coordinators.
+//!
+//! `zdoc-sync` keeps the canonical [`Store`] and serves it as JSON over plain
+//! HTTP. Each app can pull it, push its own over it, and send assignment
+//! changes as they are made, so coordinators at different locations see each
+//! other's assignments. Apps follow the change feed, a long poll, to hear of
+//! other coordinators' changes as they happen.
+//!
+//! | Route                   | Body                 | Answer                     |
+//! |-------------------------|----------------------|----------------------------|
+//! | `GET /store`            |                      | the store                  |
+//! | `PUT /store`            | the store            | nothing                    |
+//! | `POST /assignments`     | [`AssignmentChange`] | the task, as it now stands |
+//! | `GET /changes?since=n`  |                      | [`Changes`]                |
+//!
+//! Each task has a version that goes up with every assignment, and a change
+//! is only made if it was based on the version the server has. Otherwise the
+//! coordinator who got there second is told, with the task as it now stands,
+//! rather than silently undoing the other's assignment. Pushing a whole store
+//! is refused the same way if the server has assignments it lacks.
+//!
+//! Failures answer `{"error": "...", "task": ...}`, with the task only for
+//! conflicts.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use chrono::NaiveDate;
+
+use super::delivery::{connect, split_url};
+use super::{CaretakerId, ClientId, Error, Modified, PlanId, ServiceId, Store, Task, TaskId};
+
+/// The address the server listens on unless told otherwise.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+/// How long a change feed request waits for something to happen before
+/// answering that nothing did. Kept under the client's read timeout.
+pub const POLL_WAIT: Duration = Duration::from_secs(8);
+
+/// The largest request body the server reads, well over any store's size.
+const MAX_BODY: usize = 8 * 1024 * 1024;
+
+/// A task's assignee changing, as sent to the server.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct AssignmentChange {
+    /// The app's id for the task, which only means the same task on the
+    /// server if `client`, `service` and `date` agree
+    pub task: TaskId,
+    pub client: ClientId,
+    pub service: ServiceId,
+    pub date: Option<NaiveDate>,
+    /// The care plan entry and day the task was generated for. Each app
+    /// generates its own copy, so the server finds its copy by these.
+    #[serde(default)]
+    pub occurrence: Option<(PlanId, NaiveDate)>,
+    /// The task's [`version`](Task::version) the change was made to
+    #[serde(default)]
+    pub version: u32,
+    /// The new assignee, `None` when unassigned
+    pub caretaker: Option<CaretakerId>,
+    pub by: String,
+    /// The assignee and stamp before, to put back if the server refuses it
+    #[serde(default)]
+    pub previous: Option<CaretakerId>,
+    #[serde(default)]
+    pub previous_modified: Option<Modified>,
+}
+
+impl AssignmentChange {
+    /// Whether `task` is the one the change was made to.
+    fn identifies(&self, task: &Task) -> bool {
+        task.client == self.client && task.service == self.service && task.date == self.date
+    }
+}
+
+/// What changed on the server since an app last asked.
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Changes {
+    /// Ask for changes since this next time
+    pub seq: u64,
+    /// The whole store was replaced, or the server restarted, so pull it
+    /// rather than go by `tasks`
+    pub replaced: bool,
+    /// Tasks whose assignee changed, as they now stand
+    pub tasks: Vec<Task>,
+}
+
+/// Why the server didn't make an assignment change.
+#[derive(Debug)]
+pub enum Rejection {
+    /// Someone else changed the task first, this is how they left it
+    Conflict(Box<Task>),
+    /// The server couldn't be reached, so it is worth trying again later
+    Unreachable(String),
+    Failed(String),
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::Conflict(task) => write!(f, "{}", Error::Stale(task.id)),
+            Rejection::Unreachable(err) => write!(f, "couldn't reach the server: {err}"),
+            Rejection::Failed(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Failure {
+    error: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<Box<Task>>,
+    /// There was no answer at all
+    #[serde(skip)]
+    unreachable: bool,
+}
+
+impl Store {
+    /// The assignment changes made since the last call, oldest first.
+    pub fn take_assignment_changes(&mut self) -> Vec<AssignmentChange> {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Make an assignment change sent by another app, as whoever made it,
+    /// returning the task and whether it changed. It is refused as
+    /// [`Error::UnknownTask`] if this store has no task with the same client,
+    /// service and date, and as [`Error::Stale`] if the task has been
+    /// reassigned since the app's copy, unless it already has the assignee
+    /// asked for.
+    pub fn apply_assignment(&mut self, change: &AssignmentChange) -> Result<(&Task, bool), Error> {
+        let found = match change.occurrence {
+            Some((plan, date)) => {
+                self.generate_tasks(date);
+                self.tasks
+                    .iter()
+                    .find(|t| t.plan == Some(plan) && t.date == Some(date))
+            }
+            None => self.task(change.task),
+        };
+        let task = found
+            .filter(|t| change.identifies(t))
+            .map(|t| t.id)
+            .ok_or(Error::UnknownTask(change.task))?;
+        let current = self.task(task).ok_or(Error::UnknownTask(task))?;
+        let unchanged = current.assignee == change.caretaker;
+        if !unchanged && current.version != change.version {
+            return Err(Error::Stale(task));
+        }
+        if unchanged {
+            return Ok((self.task(task).ok_or(Error::UnknownTask(task))?, false));
+        }
+
+        let user = std::mem::replace(&mut self.user, change.by.clone());
+        let result = match change.caretaker {
+            Some(caretaker) => self.assign(task, caretaker),
+            None => self.unassign(task).map(|_| ()),
+        };
+        self.user = user;
+        // It came from elsewhere, so there's nothing to share
+        self.changes.clear();
+
+        result?;
+        Ok((self.task(task).ok_or(Error::UnknownTask(task))?, true))
+    }
+
+    /// Whether replacing `server` with this store would lose an assignment
+    /// made there, i.e. a task it has changed is missing here or at an older
+    /// version.
+    pub fn would_undo(&self, server: &Store) -> bool {
+        server.tasks.iter().filter(|t| t.version > 0).any(|theirs| {
+            self.task(theirs.id)
+                .filter(|t| t.client == theirs.client && t.service == theirs.service)
+                .map_or(true, |t| t.version < theirs.version)
+        })
+    }
+
+    /// Undo a change the server refused, unless the task has been changed
+    /// again since. Returns whether it was undone.
+    pub fn revert_assignment(&mut self, change: &AssignmentChange) -> bool {
+        let Some(t) = self
+            .task_mut(change.task)
+            .filter(|t| t.version == change.version + 1 && t.assignee == change.caretaker)
+        else {
+            return false;
+        };
+        t.assignee = change.previous;
+        t.modified = change.previous_modified.clone();
+        t.version = change.version;
+        true
+    }
+
+    /// Take on the assignee a task has on the server, without sharing it
+    /// again. Returns the id of this store's copy, which is added if there
+    /// wasn't one and its id is free.
+    pub fn synced_task(&mut self, server: &Task) -> Option<TaskId> {
+        let local = match server.plan.zip(server.date) {
+            Some((plan, date)) => self
+                .tasks
+                .iter()
+                .find(|t| t.plan == Some(plan) && t.date == Some(date)),
+            None => self.task(server.id),
+        }
+        .filter(|t| t.client == server.client && t.service == server.service)
+        .map(|t| t.id);
+
+        match local {
+            Some(id) => {
+                if let Some(t) = self.task_mut(id) {
+                    t.assignee = server.assignee;
+                    t.modified = server.modified.clone();
+                    t.version = server.version;
+                }
+                Some(id)
+            }
+            None if self.task(server.id).is_none() => {
+                self.next_id = self.next_id.max(server.id.0);
+                self.tasks.push(server.clone());
+                Some(server.id)
+            }
+            None => None,
+        }
+    }
+}
+
+/// A `zdoc-sync` server, talked to over blocking sockets.
+pub struct Remote {
+    /// e.g. `http://127.0.0.1:7878`
+    pub url: String,
+}
+
+impl Remote {
+    /// The server's store.
+    pub fn pull(&self) -> Result<Store, String> {
+        let body = self.request("GET", "/store", None).map_err(|f| f.error)?;
+        serde_json::from_str(&body).map_err(|e| format!("the server sent a bad store: {e}"))
+    }
+
+    /// Replace the server's store with `store`.
+    pub fn push(&self, store: &Store) -> Result<(), String> {
+        let body = serde_json::to_string(store).map_err(|e| e.to_string())?;
+        self.request("PUT", "/store", Some(body))
+            .map(|_| ())
+            .map_err(|f| f.error)
+    }
+
+    /// Make `change` on the server, returning the task as it is there now.
+    pub fn assign(&self, change: &AssignmentChange) -> Result<Task, Rejection> {
+        let body = serde_json::to_string(change).map_err(|e| Rejection::Failed(e.to_string()))?;
+        let body = self
+            .request("POST", "/assignments", Some(body))
+            .map_err(|f| match f.task {
+                Some(task) => Rejection::Conflict(task),
+                None if f.unreachable => Rejection::Unreachable(f.error),
+                None => Rejection::Failed(f.error),
+            })?;
+        serde_json::from_str(&body)
+            .map_err(|e| Rejection::Failed(format!("the server sent a bad task: {e}")))
+    }
+
+    /// What changed after `since`, waiting up to [`POLL_WAIT`] for something
+    /// to.
+    pub fn changes(&self, since: u64) -> Result<Changes, String> {
+        let body = self
+            .request("GET", &format!("/changes?since={since}"), None)
+            .map_err(|f| f.error)?;
+        serde_json::from_str(&body).map_err(|e| format!("the server sent bad changes: {e}"))
+    }
+
+    /// Send one request, returning the body of a successful answer.
+    fn request(&self, method: &str, route: &str, body: Option<String>) -> Result<String, Failure> {
+        let failed = |error: String| Failure {
+            error,
+            task: None,
+            unreachable: false,
+        };
+        let unreachable = |error: String| Failure {
+            error,
+            task: None,
+            unreachable: true,
+        };
+        let (host, path) = split_url(&self.url).map_err(failed)?;
+        let path = format!("{}{route}", path.trim_end_matches('/'));
+        let address = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{host}:80")
+        };
+        let body = body.unwrap_or_default();
+        let request = format!(
+            "{method} {path} HTTP/1.1\r\n\
+             Host: {host}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n\
+             {body}",
+            body.len()
+        );
+        let mut stream = connect(&address).map_err(unreachable)?;
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| unreachable(e.to_string()))?;
+
+        let mut answer = String::new();
+        stream
+            .read_to_string(&mut answer)
+            .map_err(|e| unreachable(e.to_string()))?;
+        let (head, body) = answer.split_once("\r\n\r\n").unwrap_or((&answer, ""));
+        let status = head.lines().next().unwrap_or_default();
+        let code: u16 = status
+            .split_whitespace()
+            .nth(1)
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(|| failed(format!("the server sent \"{}\"", status.trim())))?;
+
+        if (200..300).contains(&code) {
+            Ok(body.to_string())
+        } else {
+            Err(serde_json::from_str(body)
+                .unwrap_or_else(|_| failed(format!("the server answered {}", status.trim()))))
+        }
+    }
+}
+
+/// An answer to one request.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn ok(value: &impl serde::Serialize) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(err) => Self::failed(500, err.to_string(), None),
+        }
+    }
+
+    fn failed(status: u16, error: String, task: Option<Task>) -> Self {
+        let failure = Failure {
+            error,
+            task: task.map(Box::new),
+            unreachable: false,
+        };
+        let body = serde_json::to_string(&failure).unwrap_or_default();
+        Self { status, body }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            204 => "No Content",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// What answering a request did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// Nothing changed
+    Read,
+    /// The store changed, so save it and wake up the change feed
+    Changed,
+    /// The change feed had nothing new, so wait and ask again
+    NothingNew,
+}
+
+/// The server's side: the canonical store and when each task last changed.
+pub(crate) struct Server {
+    store: Store,
+    /// The number of the latest change
+    seq: u64,
+    /// The number of the change each task last had
+    changed: BTreeMap<TaskId, u64>,
+    /// The number of the change that last replaced the whole store
+    replaced: u64,
+}
+
+impl Server {
+    /// Changes are numbered on from `start`, which should be more than any
+    /// number a previous run gave out, e.g. the time, so apps following the
+    /// feed notice the restart and pull everything.
+    pub fn new(store: Store, start: u64) -> Self {
+        Self {
+            store,
+            seq: start,
+            changed: BTreeMap::new(),
+            replaced: start,
+        }
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Answer a request.
+    pub fn respond(&mut self, method: &str, path: &str, body: &str) -> (Response, Outcome) {
+        let (route, query) = path.split_once('?').unwrap_or((path, ""));
+        match (method, route) {
+            ("GET", "/store") => (Response::ok(&self.store), Outcome::Read),
+            ("PUT", "/store") => match serde_json::from_str::<Store>(body) {
+                Ok(pushed) if pushed.would_undo(&self.store) => {
+                    let error = "the server has assignments this copy doesn't, pull first";
+                    (
+                        Response::failed(409, error.to_string(), None),
+                        Outcome::Read,
+                    )
+                }
+                Ok(pushed) => {
+                    self.store = pushed;
+                    self.seq += 1;
+                    self.replaced = self.seq;
+                    self.changed.clear();
+                    let ok = Response {
+                        status: 204,
+                        body: String::new(),
+                    };
+                    (ok, Outcome::Changed)
+                }
+                Err(err) => (
+                    Response::failed(400, format!("bad store: {err}"), None),
+                    Outcome::Read,
+                ),
+            },
+            ("POST", "/assignments") => {
+                let change: AssignmentChange = match serde_json::from_str(body) {
+                    Ok(change) => change,
+                    Err(err) => {
+                        let bad = Response::failed(400, format!("bad change: {err}"), None);
+                        return (bad, Outcome::Read);
+                    }
+                };
+                match self.store.apply_assignment(&change) {
+                    Ok((task, false)) => (Response::ok(task), Outcome::Read),
+                    Ok((task, true)) => {
+                        let response = Response::ok(task);
+                        self.seq += 1;
+                        self.changed.insert(task.id, self.seq);
+                        (response, Outcome::Changed)
+                    }
+                    Err(err @ Error::UnknownTask(_)) => {
+                        (Response::failed(404, err.to_string(), None), Outcome::Read)
+                    }
+                    Err(err @ Error::Stale(task)) => {
+                        let task = self.store.task(task).cloned();
+                        (Response::failed(409, err.to_string(), task), Outcome::Read)
+                    }
+                    Err(err) => (Response::failed(409, err.to_string(), None), Outcome::Read),
+                }
+            }
+            ("GET", "/changes") => {
+                let since = query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("since="))
+                    .and_then(|since| since.parse().ok())
+                    .unwrap_or(0);
+                let changes = self.changes(since);
+                let outcome = if changes.replaced || !changes.tasks.is_empty() {
+                    Outcome::Read
+                } else {
+                    Outcome::NothingNew
+                };
+                (Response::ok(&changes), outcome)
+            }
+            (_, "/store" | "/assignments" | "/changes") => (
+                Response::failed(405, format!("{method} isn't allowed on {route}"), None),
+                Outcome::Read,
+            ),
+            _ => (
+                Response::failed(404, format!("nothing at {route}"), None),
+                Outcome::Read,
+            ),
+        }
+    }
+
+    fn changes(&self, since: u64) -> Changes {
+        if since < self.replaced || since > self.seq {
+            return Changes {
+                seq: self.seq,
+                replaced: true,
+                tasks: Vec::new(),
+            };
+        }
+        Changes {
+            seq: self.seq,
+            replaced: false,
+            tasks: self
+                .changed
+                .iter()
+                .filter(|&(_, &seq)| seq > since)
+                .filter_map(|(&id, _)| self.store.task(id).cloned())
+                .collect(),
+        }
+    }
+}
+
+/// Serve the store kept in the SQLite file at `database` on `address` until
+/// the process is stopped. Each connection gets its own thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn serve(address: &str, database: &std::path::Path) -> Result<(), String> {
+    use std::net::TcpListener;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::task::Poll;
+    use std::time::Instant;
+
+    use super::storage::{sqlite::Sqlite, Backend};
+
+    let mut backend = Sqlite::open(database).map_err(|e| format!("{}: {e}", database.display()))?;
+    let store = match backend.load() {
+        Poll::Ready(loaded) => loaded.map_err(|e| e.to_string())?.unwrap_or_default(),
+        Poll::Pending => unreachable!("SQLite loads straight away"),
+    };
+    let start = chrono::Utc::now().timestamp_millis().max(0) as u64;
+    let shared = Arc::new((
+        Mutex::new((Server::new(store, start), backend)),
+        Condvar::new(),
+    ));
+
+    let listener = TcpListener::bind(address).map_err(|e| format!("{address}: {e}"))?;
+    tracing::info!("Serving {} on {address}", database.display());
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!("Couldn't accept a connection: {err}");
+                continue;
+            }
+        };
+        let shared = shared.clone();
+        std::thread::spawn(move || {
+            let answer = |method: &str, path: &str, body: &str| {
+                let (lock, wake) = &*shared;
+                let mut guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+                let deadline = Instant::now() + POLL_WAIT;
+                loop {
+                    let (server, backend) = &mut *guard;
+                    let (response, outcome) = server.respond(method, path, body);
+                    match outcome {
+                        Outcome::Read => return response,
+                        Outcome::Changed => {
+                            wake.notify_all();
+                            if let Err(err) = backend.save(server.store()) {
+                                tracing::error!("Couldn't save: {err}");
+                                return Response::failed(
+                                    500,
+                                    format!("couldn't save: {err}"),
+                                    None,
+                                );
+                            }
+                            return response;
+                        }
+                        Outcome::NothingNew => {
+                            let left = deadline.saturating_duration_since(Instant::now());
+                            if left.is_zero() {
+                                return response;
+                            }
+                            guard = wake
+                                .wait_timeout(guard, left)
+                                .unwrap_or_else(|e| e.into_inner())
+                                .0;
+                        }
+                    }
+                }
+            };
+            if let Err(err) = handle(stream, answer) {
+                tracing::warn!("Couldn't answer a request: {err}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Read one request from `stream` and write back `answer`'s response.
+#[cfg(not(target_arch = "wasm32"))]
+fn handle(
+    mut stream: std::net::TcpStream,
+    answer: impl FnOnce(&str, &str, &str) -> Response,
+) -> Result<(), String> {
+    use std::io::{BufRead, BufReader};
+
+    let mut reader = BufReader::new(&mut stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(path)) = (words.next(), words.next()) else {
+        return Err(format!("bad request line \"{}\"", line.trim()));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).map_err(|e| e.to_string())?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().map_err(|_| "bad Content-Length")?;
+            }
+        }
+    }
+    let response = if length > MAX_BODY {
+        let error = format!("bodies are limited to {MAX_BODY} bytes");
+        Response::failed(413, error, None)
+    } else {
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+        let body = String::from_utf8(body).map_err(|e| e.to_string())?;
+        answer(&method, &path, &body)
+    };
+    tracing::debug!("{method} {path} {}", response.status);
+    let head = format!(
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.body.len()
+    );
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|()| stream.write_all(response.body.as_bytes()))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(server: &mut Server, change: &AssignmentChange) -> (Response, Outcome) {
+        let body = serde_json::to_string(change).unwrap();
+        server.respond("POST", "/assignments", &body)
+    }
+
+    /// Ann assigning `caretaker` to her copy of `task`.
+    fn change(task: &Task, caretaker: Option<CaretakerId>) -> AssignmentChange {
+        AssignmentChange {
+            task: task.id,
+            client: task.client,
+            service: task.service,
+            date: task.date,
+            occurrence: task.plan.zip(task.date),
+            version: task.version,
+            caretaker,
+            by: "Ann".to_string(),
+            previous: task.assignee,
+            previous_modified: task.modified.clone(),
+        }
+    }
+
+    fn changes(server: &mut Server, since: u64) -> Changes {
+        let (response, _) = server.respond("GET", &format!("/changes?since={since}"), "");
+        serde_json::from_str(&response.body).unwrap()
+    }
+
+    #[test]
+    fn assignments_are_stamped_and_shared() {
+        let mut store = Store::sample();
+        let jim = store.caretaker_named("Jim Butz").unwrap().id;
+        let task = store.tasks()[0].id;
+
+        store.set_user("Ann");
+        store.assign(task, jim).unwrap();
+        store.unassign(task).unwrap();
+        // Nothing to clear, so nothing changes
+        store.unassign(task).unwrap();
+
+        let modified = store.task(task).unwrap().modified.clone().unwrap();
+        assert_eq!(modified.by, "Ann");
+        assert_eq!(store.task(task).unwrap().version, 2);
+        let changes = store.take_assignment_changes();
+        assert_eq!(
+            changes
+                .iter()
+                .map(|c| (c.caretaker, c.version))
+                .collect::<Vec<_>>(),
+            [(Some(jim), 0), (None, 1)]
+        );
+        assert!(store.take_assignment_changes().is_empty());
+    }
+
+    #[test]
+    fn the_server_applies_changes_to_its_own_copy_of_a_task() {
+        let mut server = Server::new(Store::sample(), 1);
+        let mut app: Store =
+            serde_json::from_str(&server.respond("GET", "/store", "").0.body).unwrap();
+        let jim = app.caretaker_named("Jim Butz").unwrap().id;
+
+        // Each side generates tomorrow's tasks on its own, so their ids differ
+        let tomorrow = app.tasks()[0].date.unwrap().succ_opt().unwrap();
+        server.store.add_location("Somewhere");
+        app.generate_tasks(tomorrow);
+        let task = app
+            .tasks()
+            .iter()
+            .find(|t| t.date == Some(tomorrow))
+            .unwrap();
+        let (task, plan) = (task.id, task.plan);
+
+        app.set_user("Ann");
+        app.assign(task, jim).unwrap();
+        let (response, outcome) = post(&mut server, &app.take_assignment_changes()[0]);
+        assert_eq!(response.status, 200);
+        assert_eq!(outcome, Outcome::Changed);
+
+        let on_server: Task = serde_json::from_str(&response.body).unwrap();
+        assert_ne!(on_server.id, task);
+        assert_eq!(on_server.plan, plan);
+        assert_eq!(on_server.assignee, Some(jim));
+        assert_eq!(on_server.modified.clone().unwrap().by, "Ann");
+        assert!(server.store.take_assignment_changes().is_empty());
+
+        assert_eq!(app.synced_task(&on_server), Some(task));
+        assert_eq!(app.task(task).unwrap().version, on_server.version);
+    }
+
+    #[test]
+    fn a_task_the_server_numbered_differently_is_never_mistaken_for_another() {
+        let mut server = Server::new(Store::sample(), 1);
+        let mut app: Store =
+            serde_json::from_str(&server.respond("GET", "/store", "").0.body).unwrap();
+        let dave = app.caretaker_named("Dave Butz").unwrap().id;
+        let peter = app.client_named("Peter Groot").unwrap().id;
+        let angela = app.client_named("Angela Johnson").unwrap().id;
+        let verbal = app.service_named("Verbal Therapy").unwrap().id;
+
+        // Both sides add a task of their own, which get the same id
+        let theirs = server.store.add_task(peter, verbal).unwrap();
+        let mine = app.add_task(angela, verbal).unwrap();
+        assert_eq!(theirs, mine);
+
+        app.assign(mine, dave).unwrap();
+        let (response, outcome) = post(&mut server, &app.take_assignment_changes()[0]);
+        assert_eq!((response.status, outcome), (404, Outcome::Read));
+        assert_eq!(server.store().task(theirs).unwrap().assignee, None);
+    }
+
+    #[test]
+    fn refused_changes_are_undone_unless_changed_again() {
+        let mut store = Store::sample();
+        let jim = store.caretaker_named("Jim Butz").unwrap().id;
+        let dave = store.caretaker_named("Dave Butz").unwrap().id;
+        let task = store.tasks()[0].id;
+
+        store.set_user("Ann");
+        store.assign(task, jim).unwrap();
+        let before = store.task(task).unwrap().clone();
+        store.assign(task, dave).unwrap();
+        let changes = store.take_assignment_changes();
+
+        // Dave came later, so Jim's refusal leaves him
+        assert!(!store.revert_assignment(&changes[0]));
+        assert!(store.revert_assignment(&changes[1]));
+        let t = store.task(task).unwrap();
+        assert_eq!(t.assignee, Some(jim));
+        assert_eq!(t.modified, before.modified);
+        assert_eq!(t.version, before.version);
+        assert!(store.take_assignment_changes().is_empty());
+    }
+
+    #[test]
+    fn the_second_of_two_racing_changes_is_refused() {
+        let mut server = Server::new(Store::sample(), 1);
+        let body = server.respond("GET", "/store", "").0.body;
+        let (mut ann, mut bob): (Store, Store) = (
+            serde_json::from_str(&body).unwrap(),
+            serde_json::from_str(&body).unwrap(),
+        );
+        let task = ann.tasks()[0].id;
+        let jim = ann.caretaker_named("Jim Butz").unwrap().id;
+        let dave = ann.caretaker_named("Dave Butz").unwrap().id;
+
+        ann.assign(task, jim).unwrap();
+        bob.assign(task, dave).unwrap();
+        assert_eq!(
+            post(&mut server, &ann.take_assignment_changes()[0])
+                .0
+                .status,
+            200
+        );
+
+        let (response, outcome) = post(&mut server, &bob.take_assignment_changes()[0]);
+        assert_eq!((response.status, outcome), (409, Outcome::Read));
+        let failure: Failure = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(failure.task.unwrap().assignee, Some(jim));
+        assert_eq!(server.store().task(task).unwrap().assignee, Some(jim));
+    }
+
+    #[test]
+    fn the_feed_has_the_tasks_changed_since() {
+        let mut server = Server::new(Store::sample(), 10);
+        let jim = server.store.caretaker_named("Jim Butz").unwrap().id;
+        let task = server.store.tasks()[0].id;
+
+        // A new follower is told to pull everything first
+        let first = changes(&mut server, 0);
+        assert!(first.replaced);
+        assert_eq!(
+            server.respond("GET", "/changes?since=10", "").1,
+            Outcome::NothingNew
+        );
+
+        let change = change(server.store.task(task).unwrap(), Some(jim));
+        post(&mut server, &change);
+        let next = changes(&mut server, first.seq);
+        assert!(!next.replaced);
+        assert_eq!(next.tasks.len(), 1);
+        assert_eq!(next.tasks[0].assignee, Some(jim));
+        assert!(changes(&mut server, next.seq).tasks.is_empty());
+
+        let store = serde_json::to_string(server.store()).unwrap();
+        assert_eq!(server.respond("PUT", "/store", &store).1, Outcome::Changed);
+        assert!(changes(&mut server, next.seq).replaced);
+    }
+
+    #[test]
+    fn pushing_an_older_copy_is_refused() {
+        let mut server = Server::new(Store::sample(), 1);
+        let stale = server.respond("GET", "/store", "").0.body;
+        let jim = server.store.caretaker_named("Jim Butz").unwrap().id;
+        let task = server.store.tasks()[0].clone();
+        post(&mut server, &change(&task, Some(jim)));
+
+        let (response, outcome) = server.respond("PUT", "/store", &stale);
+        assert_eq!((response.status, outcome), (409, Outcome::Read));
+        assert_eq!(server.store().task(task.id).unwrap().assignee, Some(jim));
+
+        // Once pulled, it can be pushed back
+        let fresh = server.respond("GET", "/store", "").0.body;
+        assert_eq!(server.respond("PUT", "/store", &fresh).1, Outcome::Changed);
+    }
+
+    #[test]
+    fn a_server_that_cant_be_reached_is_told_apart_from_a_refusal() {
+        // Nothing listens on a port that was just given up
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        let remote = Remote {
+            url: format!("http://{address}"),
+        };
+        let change = change(&Store::sample().tasks()[0], None);
+        assert!(matches!(
+            remote.assign(&change),
+            Err(Rejection::Unreachable(_))
+        ));
+        let bad = Remote {
+            url: "ftp://example.com".to_string(),
+        };
+        assert!(matches!(bad.assign(&change), Err(Rejection::Failed(_))));
+    }
+
+    #[test]
+    fn oversized_bodies_are_refused_unread() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle(stream, |_, _, _| unreachable!("the body is never read")).unwrap();
+        });
+
+        let mut stream = std::net::TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "PUT /store HTTP/1.1\r\nContent-Length: 99999999999999\r\n\r\n"
+        )
+        .unwrap();
+        let mut answer = String::new();
+        stream.read_to_string(&mut answer).unwrap();
+        server.join().unwrap();
+        assert!(answer.starts_with("HTTP/1.1 413 "), "{answer}");
+    }
+
+    #[test]
+    fn refused_changes_are_reported() {
+        let mut server = Server::new(Store::sample(), 1);
+        let mut task = server.store.tasks()[0].clone();
+        task.id = TaskId(9999);
+        task.plan = None;
+        let (response, outcome) = post(&mut server, &change(&task, None));
+        assert_eq!((response.status, outcome), (404, Outcome::Read));
+
+        let (response, _) = server.respond("PUT", "/store", "not a store");
+        assert_eq!(response.status, 400);
+        assert_eq!(server.respond("GET", "/nowhere", "").0.status, 404);
+    }
+}
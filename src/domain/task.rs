@@ -0,0 +1,90 @@
+use super::{CaretakerId, ClientId, LocationId, PlanId, ServiceId, TaskId};
+
+/// A service to be given to a client, and who it is assigned to.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Task {
+    pub id: TaskId,
+    pub client: ClientId,
+    pub service: ServiceId,
+    /// Free text detail, e.g. "after lunch"
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub location: Option<LocationId>,
+    /// The day the task is scheduled for, undated tasks show on every day
+    #[serde(default)]
+    pub date: Option<chrono::NaiveDate>,
+    #[serde(default)]
+    pub start: Option<chrono::NaiveTime>,
+    #[serde(default)]
+    pub end: Option<chrono::NaiveTime>,
+    pub assignee: Option<CaretakerId>,
+    /// The care plan entry it was generated from, if any
+    #[serde(default)]
+    pub plan: Option<PlanId>,
+    /// Who last changed the assignee, and when
+    #[serde(default)]
+    pub modified: Option<Modified>,
+    /// Goes up each time the assignee changes, so a change made to an older
+    /// copy of the task can be caught
+    #[serde(default)]
+    pub version: u32,
+}
+
+/// Who made a change, and when.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Modified {
+    pub by: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Task {
+    pub(super) fn new(id: TaskId, client: ClientId, service: ServiceId) -> Self {
+        Self {
+            id,
+            client,
+            service,
+            description: String::new(),
+            location: None,
+            date: None,
+            start: None,
+            end: None,
+            assignee: None,
+            plan: None,
+            modified: None,
+            version: 0,
+        }
+    }
+
+    /// Whether the task belongs on the schedule for `date`.
+    pub fn is_on(&self, date: chrono::NaiveDate) -> bool {
+        self.date.map_or(true, |d| d == date)
+    }
+
+    /// Whether the two tasks take place at the same time, so one person can't
+    /// do both. Tasks without a start time never overlap.
+    pub fn overlaps(&self, other: &Task) -> bool {
+        if matches!((self.date, other.date), (Some(a), Some(b)) if a != b) {
+            return false;
+        }
+        let (Some(a_start), Some(b_start)) = (self.start, other.start) else {
+            return false;
+        };
+        let a_end = self.end.unwrap_or(a_start);
+        let b_end = other.end.unwrap_or(b_start);
+
+        a_start == b_start || (a_start < b_end && b_start < a_end)
+    }
+
+    /// "09:00-10:00", or whichever half is known.
+    pub fn time_text(&self) -> String {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => {
+                format!("{}-{}", start.format("%H:%M"), end.format("%H:%M"))
+            }
+            (Some(start), None) => start.format("%H:%M").to_string(),
+            (None, Some(end)) => format!("until {}", end.format("%H:%M")),
+            (None, None) => String::new(),
+        }
+    }
+}
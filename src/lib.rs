@@ -1,6 +1,9 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+mod domain;
 
 // Re-exports
 pub use app::WrapApp;
+#[cfg(not(target_arch = "wasm32"))]
+pub use domain::sync::{serve, DEFAULT_ADDRESS};